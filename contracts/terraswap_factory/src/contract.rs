@@ -1,20 +1,47 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError,
-    StdResult, SubMsg, WasmMsg,
+    from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
 };
 
-use crate::querier::query_liquidity_token;
+use crate::querier::{query_liquidity_token, query_pair_contract_info};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{pair_key, read_pairs, Config, TmpPairInfo, CONFIG, PAIRS, TMP_PAIR_INFO};
+use crate::state::{
+    index_pairs, pair_key, read_asset_whitelist, read_pairs, read_pairs_by_asset,
+    read_pairs_of_type, remove_pair, save_pair, Config, InitialLiquidity, LegacyConfig,
+    TmpPairInfo, ASSET_BLACKLIST, ASSET_WHITELIST, CONFIG, LEGACY_CONFIG, NATIVE_TOKEN_DECIMALS,
+    OWNERSHIP_PROPOSAL, PAIRS, PAIRS_BY_ADDRESS, PAIRS_BY_LIQUIDITY_TOKEN, PAIR_CREATION_ID,
+    TMP_PAIR_INFOS,
+};
 
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::U64Key;
 use protobuf::Message;
-use terraswap::asset::{AssetInfo, PairInfo, PairInfoRaw};
+use std::str::FromStr;
+use terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
 use terraswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse, QueryMsg,
+    AssetWhitelistResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    NativeTokenDecimalsResponse, PairTypeConfig, PairsResponse, QueryMsg,
+};
+use terraswap::ownership::{claim_ownership, propose_new_owner};
+use terraswap::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    MigrateMsg as PairMigrateMsg, PairType, DEFAULT_PAIR_TYPE,
 };
-use terraswap::pair::InstantiateMsg as PairInstantiateMsg;
+use terraswap::querier::query_token_decimals;
+use terraswap::version::assert_migrate_version;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:terraswap-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Default commission rate == 0.3%
+const DEFAULT_COMMISSION_RATE: &str = "0.003";
+
+/// Decimals of the native tokens without registered decimals, the precision of the Terra denoms
+const DEFAULT_NATIVE_TOKEN_DECIMALS: u8 = 6;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -23,10 +50,37 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let commission_rate = match msg.commission_rate {
+        Some(commission_rate) => commission_rate,
+        None => Decimal::from_str(DEFAULT_COMMISSION_RATE)?,
+    };
+    assert_commission_rate(commission_rate)?;
+
+    let protocol_fee_share = msg.protocol_fee_share.unwrap_or_else(Decimal::zero);
+    assert_protocol_fee_share(protocol_fee_share)?;
+
+    let fee_collector = if let Some(fee_collector) = msg.fee_collector {
+        Some(deps.api.addr_canonicalize(&fee_collector)?)
+    } else {
+        None
+    };
+
     let config = Config {
         owner: deps.api.addr_canonicalize(info.sender.as_str())?,
         token_code_id: msg.token_code_id,
-        pair_code_id: msg.pair_code_id,
+        pair_types: vec![PairTypeConfig {
+            pair_type: DEFAULT_PAIR_TYPE.to_string(),
+            code_id: msg.pair_code_id,
+            curve: PairType::Xyk,
+            commission_rate,
+        }],
+        protocol_fee_share,
+        fee_collector,
+        permissioned: false,
+        creation_fee: None,
+        treasury: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -38,22 +92,146 @@ pub fn instantiate(
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::UpdateConfig {
-            owner,
             token_code_id,
-            pair_code_id,
-        } => execute_update_config(deps, env, info, owner, token_code_id, pair_code_id),
-        ExecuteMsg::CreatePair { asset_infos } => execute_create_pair(deps, env, info, asset_infos),
+            protocol_fee_share,
+            fee_collector,
+            permissioned,
+            creation_fee,
+            treasury,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            token_code_id,
+            protocol_fee_share,
+            fee_collector,
+            permissioned,
+            creation_fee,
+            treasury,
+        ),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdatePairType {
+            pair_type,
+            code_id,
+            curve,
+            commission_rate,
+        } => execute_update_pair_type(deps, env, info, pair_type, code_id, curve, commission_rate),
+        ExecuteMsg::RemovePairType { pair_type } => {
+            execute_remove_pair_type(deps, env, info, pair_type)
+        }
+        ExecuteMsg::CreatePair {
+            asset_infos,
+            pair_type,
+            amp,
+        } => {
+            let fee_messages = collect_creation_fee(
+                deps.as_ref(),
+                &native_assets(&info.funds),
+                Some(&info.sender),
+            )?;
+            execute_create_pair(deps, env, asset_infos, pair_type, amp, None, fee_messages)
+        }
+        ExecuteMsg::CreatePairWithLiquidity {
+            assets,
+            slippage_tolerance,
+            receiver,
+            pair_type,
+            amp,
+        } => execute_create_pair_with_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            slippage_tolerance,
+            receiver,
+            pair_type,
+            amp,
+        ),
+        ExecuteMsg::UpdatePairConfig {
+            asset_infos,
+            pair_type,
+            commission_rate,
+        } => execute_update_pair_config(deps, env, info, asset_infos, pair_type, commission_rate),
+        ExecuteMsg::RampPairAmp {
+            asset_infos,
+            pair_type,
+            future_amp,
+            future_time,
+        } => execute_ramp_pair_amp(
+            deps,
+            env,
+            info,
+            asset_infos,
+            pair_type,
+            future_amp,
+            future_time,
+        ),
+        ExecuteMsg::StopPairAmpRamp {
+            asset_infos,
+            pair_type,
+        } => execute_stop_pair_amp_ramp(deps, env, info, asset_infos, pair_type),
+        ExecuteMsg::SetPairStatus {
+            asset_infos,
+            pair_type,
+            paused,
+        } => execute_set_pair_status(deps, env, info, asset_infos, pair_type, paused),
+        ExecuteMsg::MigratePairs {
+            code_id,
+            pair_type,
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => execute_migrate_pairs(
+            deps,
+            env,
+            info,
+            code_id,
+            pair_type,
+            start_after,
+            start_after_pair_type,
+            limit,
+        ),
+        ExecuteMsg::IndexPairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => execute_index_pairs(deps, env, info, start_after, start_after_pair_type, limit),
+        ExecuteMsg::DeregisterPair {
+            asset_infos,
+            pair_type,
+        } => execute_deregister_pair(deps, env, info, asset_infos, pair_type),
+        ExecuteMsg::RegisterExistingPair { pair_addr } => {
+            execute_register_existing_pair(deps, env, info, pair_addr)
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            execute_propose_new_owner(deps, env, info, owner, expires_in)
+        }
+        ExecuteMsg::ClaimOwnership {} => execute_claim_ownership(deps, env, info),
+        ExecuteMsg::DropOwnershipProposal {} => execute_drop_ownership_proposal(deps, env, info),
+        ExecuteMsg::UpdateAssetWhitelist { add, remove } => {
+            execute_update_asset_whitelist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateAssetBlacklist { add, remove } => {
+            execute_update_asset_blacklist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::AddNativeTokenDecimals { denom, decimals } => {
+            execute_add_native_token_decimals(deps, env, info, denom, decimals)
+        }
     }
 }
 
 // Only owner can execute it
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    owner: Option<String>,
     token_code_id: Option<u64>,
-    pair_code_id: Option<u64>,
+    protocol_fee_share: Option<Decimal>,
+    fee_collector: Option<String>,
+    permissioned: Option<bool>,
+    creation_fee: Option<Asset>,
+    treasury: Option<String>,
 ) -> StdResult<Response> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
@@ -62,19 +240,45 @@ pub fn execute_update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    if let Some(owner) = owner {
+    if let Some(token_code_id) = token_code_id {
+        config.token_code_id = token_code_id;
+    }
+
+    if let Some(protocol_fee_share) = protocol_fee_share {
+        assert_protocol_fee_share(protocol_fee_share)?;
+        config.protocol_fee_share = protocol_fee_share;
+    }
+
+    if let Some(fee_collector) = fee_collector {
         // validate address format
-        let _ = deps.api.addr_validate(&owner)?;
+        let _ = deps.api.addr_validate(&fee_collector)?;
 
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+        config.fee_collector = Some(deps.api.addr_canonicalize(&fee_collector)?);
     }
 
-    if let Some(token_code_id) = token_code_id {
-        config.token_code_id = token_code_id;
+    if let Some(permissioned) = permissioned {
+        config.permissioned = permissioned;
+    }
+
+    if let Some(creation_fee) = creation_fee {
+        config.creation_fee = if creation_fee.amount.is_zero() {
+            None
+        } else {
+            Some(creation_fee.to_raw(deps.api)?)
+        };
     }
 
-    if let Some(pair_code_id) = pair_code_id {
-        config.pair_code_id = pair_code_id;
+    if let Some(treasury) = treasury {
+        // validate address format
+        let _ = deps.api.addr_validate(&treasury)?;
+
+        config.treasury = Some(deps.api.addr_canonicalize(&treasury)?);
+    }
+
+    if config.creation_fee.is_some() && config.treasury.is_none() {
+        return Err(StdError::generic_err(
+            "treasury is required to charge a creation fee",
+        ));
     }
 
     CONFIG.save(deps.storage, &config)?;
@@ -82,29 +286,786 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
-// Anyone can execute it to create swap pair
-pub fn execute_create_pair(
+// Only owner can execute it
+pub fn execute_propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: u64,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let proposal = propose_new_owner(deps.api, &env, &info.sender, &owner, expires_in)?;
+    OWNERSHIP_PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("new_owner", proposal.owner.as_str()),
+        ("expires_at", &proposal.expires_at.to_string()),
+    ]))
+}
+
+// Only the proposed owner can execute it
+pub fn execute_claim_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    let new_owner = claim_ownership(&env, &info, OWNERSHIP_PROPOSAL.may_load(deps.storage)?)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    config.owner = deps.api.addr_canonicalize(new_owner.as_str())?;
+    CONFIG.save(deps.storage, &config)?;
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "claim_ownership"),
+        ("new_owner", new_owner.as_str()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_drop_ownership_proposal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "drop_ownership_proposal"))
+}
+
+// Only owner can execute it
+pub fn execute_update_pair_type(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair_type: String,
+    code_id: u64,
+    curve: PairType,
+    commission_rate: Option<Decimal>,
+) -> StdResult<Response> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        assert_commission_rate(commission_rate)?;
+    }
+
+    match config
+        .pair_types
+        .iter_mut()
+        .find(|registered| registered.pair_type == pair_type)
+    {
+        Some(registered) => {
+            // the pairs already created with the type keep their curve
+            if registered.curve != curve {
+                return Err(StdError::generic_err(format!(
+                    "Curve of pair type {} cannot be changed",
+                    pair_type
+                )));
+            }
+
+            registered.code_id = code_id;
+            if let Some(commission_rate) = commission_rate {
+                registered.commission_rate = commission_rate;
+            }
+        }
+        None => config.pair_types.push(PairTypeConfig {
+            pair_type: pair_type.clone(),
+            code_id,
+            curve,
+            commission_rate: match commission_rate {
+                Some(commission_rate) => commission_rate,
+                None => Decimal::from_str(DEFAULT_COMMISSION_RATE)?,
+            },
+        }),
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pair_type"),
+        ("pair_type", &pair_type),
+        ("code_id", &code_id.to_string()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_remove_pair_type(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair_type: String,
+) -> StdResult<Response> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if pair_type == DEFAULT_PAIR_TYPE {
+        return Err(StdError::generic_err("default pair type cannot be removed"));
+    }
+
+    let pair_types_len = config.pair_types.len();
+    config
+        .pair_types
+        .retain(|registered| registered.pair_type != pair_type);
+    if config.pair_types.len() == pair_types_len {
+        return Err(StdError::generic_err(format!(
+            "Pair type {} is not registered",
+            pair_type
+        )));
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_pair_type"),
+        ("pair_type", &pair_type),
+    ]))
+}
+
+fn find_pair_type<'a>(config: &'a Config, pair_type: &str) -> StdResult<&'a PairTypeConfig> {
+    config
+        .pair_types
+        .iter()
+        .find(|registered| registered.pair_type == pair_type)
+        .ok_or_else(|| StdError::generic_err(format!("Pair type {} is not registered", pair_type)))
+}
+
+// Only owner can execute it
+pub fn execute_update_pair_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+    commission_rate: Option<Decimal>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        assert_commission_rate(commission_rate)?;
+    }
+
+    let pair_info: PairInfo = query_pair(deps.as_ref(), asset_infos, pair_type)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.clone(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::UpdateConfig { commission_rate })?,
+        }))
+        .add_attributes(vec![
+            ("action", "update_pair_config"),
+            ("pair_contract_addr", pair_info.contract_addr.as_str()),
+        ]))
+}
+
+// Only owner can execute it
+pub fn execute_ramp_pair_amp(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+    future_amp: u64,
+    future_time: u64,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_info: PairInfo = query_pair(deps.as_ref(), asset_infos, pair_type)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.clone(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::RampAmp {
+                future_amp,
+                future_time,
+            })?,
+        }))
+        .add_attributes(vec![
+            ("action", "ramp_pair_amp"),
+            ("pair_contract_addr", pair_info.contract_addr.as_str()),
+        ]))
+}
+
+// Only owner can execute it
+pub fn execute_stop_pair_amp_ramp(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_info: PairInfo = query_pair(deps.as_ref(), asset_infos, pair_type)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.clone(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::StopRampAmp {})?,
+        }))
+        .add_attributes(vec![
+            ("action", "stop_pair_amp_ramp"),
+            ("pair_contract_addr", pair_info.contract_addr.as_str()),
+        ]))
+}
+
+// Only owner can execute it
+pub fn execute_set_pair_status(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+    paused: bool,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_key = pair_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        pair_type.as_deref().unwrap_or(DEFAULT_PAIR_TYPE),
+    );
+    let mut pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    pair_info.paused = paused;
+    PAIRS.save(deps.storage, &pair_key, &pair_info)?;
+
+    let pair_contract = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::SetStatus { paused })?,
+        }))
+        .add_attributes(vec![
+            ("action", "set_pair_status"),
+            ("pair_contract_addr", pair_contract.as_str()),
+            ("paused", &paused.to_string()),
+        ]))
+}
+
+// Only owner can execute it
+pub fn execute_deregister_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_key = pair_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        pair_type.as_deref().unwrap_or(DEFAULT_PAIR_TYPE),
+    );
+    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    remove_pair(deps.storage, &pair_info);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "deregister_pair"),
+        ("pair", &format!("{}-{}", asset_infos[0], asset_infos[1])),
+        (
+            "pair_contract_addr",
+            deps.api.addr_humanize(&pair_info.contract_addr)?.as_str(),
+        ),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_register_existing_pair(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair_info: PairInfo = query_pair_contract_info(deps.as_ref(), pair_addr.clone())?;
+    if pair_info.contract_addr != pair_addr {
+        return Err(StdError::generic_err(
+            "Pair contract address mismatch between the argument and the pair",
+        ));
+    }
+
+    // the pair reports its own type and decimals, only a registered type is accepted
+    // and the decimals are queried again like for a new pair
+    find_pair_type(&config, &pair_info.pair_type)?;
+    let asset_decimals = [
+        query_decimals(deps.as_ref(), &pair_info.asset_infos[0])?,
+        query_decimals(deps.as_ref(), &pair_info.asset_infos[1])?,
+    ];
+
+    let pair_info_raw = PairInfoRaw {
+        asset_infos: [
+            pair_info.asset_infos[0].to_raw(deps.api)?,
+            pair_info.asset_infos[1].to_raw(deps.api)?,
+        ],
+        contract_addr: deps
+            .api
+            .addr_canonicalize(pair_info.contract_addr.as_str())?,
+        liquidity_token: deps
+            .api
+            .addr_canonicalize(pair_info.liquidity_token.as_str())?,
+        paused: pair_info.paused,
+        pair_type: pair_info.pair_type.clone(),
+        asset_decimals,
+    };
+
+    if PAIRS
+        .may_load(
+            deps.storage,
+            &pair_key(&pair_info_raw.asset_infos, &pair_info_raw.pair_type),
+        )?
+        .is_some()
+    {
+        return Err(StdError::generic_err("Pair already exists"));
+    }
+
+    save_pair(deps.storage, &pair_info_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "register_existing_pair"),
+        (
+            "pair",
+            &format!("{}-{}", pair_info.asset_infos[0], pair_info.asset_infos[1]),
+        ),
+        ("pair_contract_addr", pair_info.contract_addr.as_str()),
+        ("liquidity_token_addr", pair_info.liquidity_token.as_str()),
+    ]))
+}
+
+// Only owner can execute it
+#[allow(clippy::too_many_arguments)]
+pub fn execute_migrate_pairs(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    code_id: u64,
+    pair_type: Option<String>,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // the page only holds the pairs of the type,
+    // the last one is returned to continue after it
+    let pair_type = pair_type.unwrap_or_else(|| DEFAULT_PAIR_TYPE.to_string());
+    let start_after = start_after_key(deps.as_ref(), start_after, start_after_pair_type)?;
+    let pairs: Vec<PairInfo> =
+        read_pairs_of_type(deps.storage, deps.api, &pair_type, start_after, limit)?;
+    let messages: Vec<CosmosMsg> = pairs
+        .iter()
+        .map(|pair| -> StdResult<CosmosMsg> {
+            Ok(CosmosMsg::Wasm(WasmMsg::Migrate {
+                contract_addr: pair.contract_addr.clone(),
+                new_code_id: code_id,
+                msg: to_binary(&PairMigrateMsg {
+                    factory: Some(env.contract.address.to_string()),
+                })?,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    let mut attributes = vec![
+        ("action", "migrate_pairs".to_string()),
+        ("pair_type", pair_type),
+        ("code_id", code_id.to_string()),
+        ("migrated_count", pairs.len().to_string()),
+    ];
+    if let Some(last_pair) = pairs.last() {
+        attributes.push((
+            "last_pair",
+            format!("{}-{}", last_pair.asset_infos[0], last_pair.asset_infos[1]),
+        ));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+// Only owner can execute it
+pub fn execute_index_pairs(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let start_after = start_after_key(deps.as_ref(), start_after, start_after_pair_type)?;
+    let indexed_count = index_pairs(deps.storage, start_after, limit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "index_pairs"),
+        ("indexed_count", &indexed_count.to_string()),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_update_asset_whitelist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<AssetInfo>,
+    remove: Vec<AssetInfo>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    for asset_info in add.iter() {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_WHITELIST.save(deps.storage, raw_info.as_bytes(), &raw_info)?;
+    }
+
+    for asset_info in remove.iter() {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_WHITELIST.remove(deps.storage, raw_info.as_bytes());
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_asset_whitelist"),
+        ("added", &join_asset_infos(&add)),
+        ("removed", &join_asset_infos(&remove)),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_update_asset_blacklist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<AssetInfo>,
+    remove: Vec<AssetInfo>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    for asset_info in add.iter() {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_BLACKLIST.save(deps.storage, raw_info.as_bytes(), &raw_info)?;
+    }
+
+    for asset_info in remove.iter() {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_BLACKLIST.remove(deps.storage, raw_info.as_bytes());
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_asset_blacklist"),
+        ("added", &join_asset_infos(&add)),
+        ("removed", &join_asset_infos(&remove)),
+    ]))
+}
+
+// Only owner can execute it
+pub fn execute_add_native_token_decimals(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
+    denom: String,
+    decimals: u8,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // only an existing denom can be sent
+    if !info
+        .funds
+        .iter()
+        .any(|coin| coin.denom == denom && !coin.amount.is_zero())
+    {
+        return Err(StdError::generic_err(format!(
+            "A deposit of {} is required",
+            denom
+        )));
+    }
+
+    NATIVE_TOKEN_DECIMALS.save(deps.storage, denom.as_bytes(), &decimals)?;
+
+    // the deposit is only a proof, it is sent back
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for coin in info.funds.iter().filter(|coin| !coin.amount.is_zero()) {
+        let refund = Asset {
+            info: AssetInfo::NativeToken {
+                denom: coin.denom.clone(),
+            },
+            amount: coin.amount,
+        };
+        messages.push(refund.into_msg(&deps.querier, info.sender.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "add_native_token_decimals"),
+        ("denom", &denom),
+        ("decimals", &decimals.to_string()),
+    ]))
+}
+
+fn query_decimals(deps: Deps, asset_info: &AssetInfo) -> StdResult<u8> {
+    match asset_info {
+        AssetInfo::Token { contract_addr } => {
+            query_token_decimals(&deps.querier, deps.api.addr_validate(contract_addr)?)
+        }
+        AssetInfo::NativeToken { denom } => Ok(NATIVE_TOKEN_DECIMALS
+            .may_load(deps.storage, denom.as_bytes())?
+            .unwrap_or(DEFAULT_NATIVE_TOKEN_DECIMALS)),
+    }
+}
+
+fn join_asset_infos(asset_infos: &[AssetInfo]) -> String {
+    asset_infos
+        .iter()
+        .map(|asset_info| asset_info.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::CreatePair {
+            asset_infos,
+            pair_type,
+            amp,
+        } => {
+            let paid = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: cw20_msg.amount,
+            };
+
+            // the sent token must be the creation fee, otherwise it would be stuck in the factory
+            let config: Config = CONFIG.load(deps.storage)?;
+            match config.creation_fee {
+                Some(creation_fee) if creation_fee.to_normal(deps.api)? == paid => {}
+                _ => return Err(StdError::generic_err("Sent token is not the creation fee")),
+            }
+
+            let fee_messages = collect_creation_fee(deps.as_ref(), &[paid], None)?;
+            execute_create_pair(deps, env, asset_infos, pair_type, amp, None, fee_messages)
+        }
+    }
+}
+
+fn native_assets(funds: &[Coin]) -> Vec<Asset> {
+    funds
+        .iter()
+        .map(|coin| Asset {
+            info: AssetInfo::NativeToken {
+                denom: coin.denom.clone(),
+            },
+            amount: coin.amount,
+        })
+        .collect()
+}
+
+/// Checks the `paid` assets, held by the factory, are exactly the creation fee and returns
+/// the messages forwarding it to the treasury. Token fees which are not paid are pulled
+/// from the allowance of the `payer`. Any other payment is rejected, it would be stuck
+/// in the factory.
+fn collect_creation_fee(
+    deps: Deps,
+    paid: &[Asset],
+    payer: Option<&Addr>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let paid: Vec<&Asset> = paid
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .collect();
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let creation_fee = match config.creation_fee {
+        Some(creation_fee) => creation_fee.to_normal(deps.api)?,
+        None if paid.is_empty() => return Ok(vec![]),
+        None => return Err(StdError::generic_err("No creation fee is charged")),
+    };
+
+    let treasury = match config.treasury {
+        Some(treasury) => deps.api.addr_humanize(&treasury)?,
+        None => return Err(StdError::generic_err("treasury is not set")),
+    };
+
+    match paid.as_slice() {
+        [] => {}
+        [asset] if **asset == creation_fee => {
+            return Ok(vec![creation_fee.into_msg(&deps.querier, treasury)?]);
+        }
+        _ => {
+            return Err(StdError::generic_err(format!(
+                "Only the creation fee of {} can be sent",
+                creation_fee
+            )))
+        }
+    }
+
+    match (&creation_fee.info, payer) {
+        (AssetInfo::Token { contract_addr }, Some(payer)) => {
+            Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: payer.to_string(),
+                    recipient: treasury.to_string(),
+                    amount: creation_fee.amount,
+                })?,
+                funds: vec![],
+            })])
+        }
+        _ => Err(StdError::generic_err(format!(
+            "Creation fee of {} is required",
+            creation_fee
+        ))),
+    }
+}
+
+// Anyone can execute it to create swap pair,
+// only whitelisted assets are allowed while the creation is permissioned
+pub fn execute_create_pair(
+    deps: DepsMut,
+    env: Env,
     asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+    amp: Option<u64>,
+    initial_liquidity: Option<InitialLiquidity>,
+    fee_messages: Vec<CosmosMsg>,
 ) -> StdResult<Response> {
     let config: Config = CONFIG.load(deps.storage)?;
+    let pair_type = pair_type.unwrap_or_else(|| DEFAULT_PAIR_TYPE.to_string());
+    let pair_type_config: &PairTypeConfig = find_pair_type(&config, &pair_type)?;
     let raw_infos = [
         asset_infos[0].to_raw(deps.api)?,
         asset_infos[1].to_raw(deps.api)?,
     ];
 
-    let pair_key = pair_key(&raw_infos);
+    for (asset_info, raw_info) in asset_infos.iter().zip(raw_infos.iter()) {
+        if ASSET_BLACKLIST
+            .may_load(deps.storage, raw_info.as_bytes())?
+            .is_some()
+        {
+            return Err(StdError::generic_err(format!(
+                "Asset {} is blacklisted",
+                asset_info
+            )));
+        }
+
+        if config.permissioned
+            && ASSET_WHITELIST
+                .may_load(deps.storage, raw_info.as_bytes())?
+                .is_none()
+        {
+            return Err(StdError::generic_err(format!(
+                "Asset {} is not whitelisted",
+                asset_info
+            )));
+        }
+    }
+
+    let pair_key = pair_key(&raw_infos, &pair_type);
     if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
         return Err(StdError::generic_err("Pair already exists"));
     }
 
-    TMP_PAIR_INFO.save(
+    let asset_decimals = [
+        query_decimals(deps.as_ref(), &asset_infos[0])?,
+        query_decimals(deps.as_ref(), &asset_infos[1])?,
+    ];
+
+    let creation_id = PAIR_CREATION_ID.may_load(deps.storage)?.unwrap_or(0) + 1;
+    PAIR_CREATION_ID.save(deps.storage, &creation_id)?;
+    TMP_PAIR_INFOS.save(
         deps.storage,
+        U64Key::new(creation_id),
         &TmpPairInfo {
             pair_key,
             asset_infos: raw_infos,
+            pair_type: pair_type.clone(),
+            asset_decimals,
+            initial_liquidity,
         },
     )?;
 
@@ -112,29 +1073,112 @@ pub fn execute_create_pair(
         .add_attributes(vec![
             ("action", "create_pair"),
             ("pair", &format!("{}-{}", asset_infos[0], asset_infos[1])),
+            ("pair_type", &pair_type),
         ])
         .add_submessage(SubMsg {
-            id: 1,
+            id: creation_id,
             gas_limit: None,
             msg: WasmMsg::Instantiate {
-                code_id: config.pair_code_id,
+                code_id: pair_type_config.code_id,
                 funds: vec![],
-                admin: None,
+                admin: Some(env.contract.address.to_string()),
                 label: "".to_string(),
                 msg: to_binary(&PairInstantiateMsg {
                     asset_infos,
                     token_code_id: config.token_code_id,
+                    commission_rate: Some(pair_type_config.commission_rate),
+                    pair_type: Some(pair_type_config.curve.clone()),
+                    pair_type_name: Some(pair_type.clone()),
+                    amp,
+                    asset_decimals,
                 })?,
             }
             .into(),
             reply_on: ReplyOn::Success,
-        }))
+        })
+        .add_messages(fee_messages))
+}
+
+// Anyone can execute it to create a xyk pair with its first liquidity
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_pair_with_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+    pair_type: Option<String>,
+    amp: Option<u64>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let creation_fee = match config.creation_fee {
+        Some(creation_fee) => Some(creation_fee.to_normal(deps.api)?),
+        None => None,
+    };
+
+    // the sent funds left after the native assets pay the creation fee
+    let mut fee_funds: Vec<Coin> = info.funds.clone();
+    for asset in assets.iter() {
+        let mut sent = asset.clone();
+        if let Some(creation_fee) = &creation_fee {
+            if creation_fee.info == asset.info {
+                sent.amount = sent.amount.checked_add(creation_fee.amount)?;
+            }
+        }
+        sent.assert_sent_native_token_balance(&info)?;
+
+        if let AssetInfo::NativeToken { denom } = &asset.info {
+            if let Some(coin) = fee_funds.iter_mut().find(|coin| coin.denom == *denom) {
+                coin.amount = coin.amount.checked_sub(asset.amount)?;
+            }
+        }
+    }
+
+    let fee_messages = collect_creation_fee(
+        deps.as_ref(),
+        &native_assets(&fee_funds),
+        Some(&info.sender),
+    )?;
+
+    let receiver = if let Some(receiver) = receiver {
+        deps.api.addr_validate(&receiver)?
+    } else {
+        info.sender.clone()
+    };
+
+    let initial_liquidity = InitialLiquidity {
+        assets: [assets[0].to_raw(deps.api)?, assets[1].to_raw(deps.api)?],
+        slippage_tolerance,
+        provider: deps.api.addr_canonicalize(info.sender.as_str())?,
+        receiver: deps.api.addr_canonicalize(receiver.as_str())?,
+    };
+
+    let asset_infos = [assets[0].info.clone(), assets[1].info.clone()];
+    execute_create_pair(
+        deps,
+        env,
+        asset_infos,
+        pair_type,
+        amp,
+        Some(initial_liquidity),
+        fee_messages,
+    )
 }
 
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
-    let tmp_pair_info = TMP_PAIR_INFO.load(deps.storage)?;
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    let tmp_pair_info = TMP_PAIR_INFOS.load(deps.storage, U64Key::new(msg.id))?;
+    TMP_PAIR_INFOS.remove(deps.storage, U64Key::new(msg.id));
+
+    // the same pair can be created twice in a single transaction
+    if PAIRS
+        .may_load(deps.storage, &tmp_pair_info.pair_key)?
+        .is_some()
+    {
+        return Err(StdError::generic_err("Pair already exists"));
+    }
 
     let res: MsgInstantiateContractResponse =
         Message::parse_from_bytes(msg.result.unwrap().data.unwrap().as_slice()).map_err(|_| {
@@ -144,74 +1188,346 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     let pair_contract = res.get_contract_address();
     let liquidity_token = query_liquidity_token(deps.as_ref(), Addr::unchecked(pair_contract))?;
 
-    PAIRS.save(
+    save_pair(
         deps.storage,
-        &tmp_pair_info.pair_key,
         &PairInfoRaw {
             liquidity_token: deps.api.addr_canonicalize(liquidity_token.as_str())?,
             contract_addr: deps.api.addr_canonicalize(pair_contract)?,
             asset_infos: tmp_pair_info.asset_infos,
+            paused: false,
+            pair_type: tmp_pair_info.pair_type,
+            asset_decimals: tmp_pair_info.asset_decimals,
         },
     )?;
 
-    Ok(Response::new().add_attributes(vec![
+    let messages: Vec<CosmosMsg> = match tmp_pair_info.initial_liquidity {
+        Some(initial_liquidity) => provide_initial_liquidity(
+            deps.as_ref(),
+            env,
+            Addr::unchecked(pair_contract),
+            initial_liquidity,
+        )?,
+        None => vec![],
+    };
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
         ("pair_contract_addr", pair_contract),
         ("liquidity_token_addr", liquidity_token.as_str()),
     ]))
 }
 
+/// Pulls the token assets from the provider and provides all assets to the new pair,
+/// the native assets are sent after tax
+fn provide_initial_liquidity(
+    deps: Deps,
+    env: Env,
+    pair_contract: Addr,
+    initial_liquidity: InitialLiquidity,
+) -> StdResult<Vec<CosmosMsg>> {
+    let provider = deps.api.addr_humanize(&initial_liquidity.provider)?;
+    let receiver = deps.api.addr_humanize(&initial_liquidity.receiver)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    let mut assets: Vec<Asset> = vec![];
+    for asset in initial_liquidity.assets.iter() {
+        let asset = asset.to_normal(deps.api)?;
+        match &asset.info {
+            AssetInfo::Token { contract_addr } => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: provider.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: asset.amount,
+                    })?,
+                }));
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair_contract.to_string(),
+                        amount: asset.amount,
+                        expires: None,
+                    })?,
+                }));
+                assets.push(asset);
+            }
+            AssetInfo::NativeToken { .. } => {
+                let coin = asset.deduct_tax(&deps.querier)?;
+                assets.push(Asset {
+                    info: asset.info,
+                    amount: coin.amount,
+                });
+                funds.push(coin);
+            }
+        }
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_contract.to_string(),
+        funds,
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+            assets: [assets[0].clone(), assets[1].clone()],
+            slippage_tolerance: initial_liquidity.slippage_tolerance,
+            receiver: Some(receiver.to_string()),
+            deadline: None,
+        })?,
+    }));
+
+    Ok(messages)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_binary(&query_pairs(deps, start_after, limit)?)
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => to_binary(&query_pair(deps, asset_infos, pair_type)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => to_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_pair_type,
+            limit,
+        )?),
+        QueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => to_binary(&query_pairs_by_asset(
+            deps,
+            asset_info,
+            start_after,
+            start_after_pair_type,
+            limit,
+        )?),
+        QueryMsg::PairByAddress { contract_addr } => {
+            to_binary(&query_pair_by_address(deps, contract_addr)?)
+        }
+        QueryMsg::AssetWhitelist { start_after, limit } => {
+            to_binary(&query_asset_whitelist(deps, start_after, limit)?)
+        }
+        QueryMsg::NativeTokenDecimals { denom } => {
+            to_binary(&query_native_token_decimals(deps, denom)?)
+        }
+        QueryMsg::PairByLiquidityToken { liquidity_token } => {
+            to_binary(&query_pair_by_liquidity_token(deps, liquidity_token)?)
         }
     }
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state: Config = CONFIG.load(deps.storage)?;
+    let default_pair_type: &PairTypeConfig = find_pair_type(&state, DEFAULT_PAIR_TYPE)?;
+
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         token_code_id: state.token_code_id,
-        pair_code_id: state.pair_code_id,
+        pair_code_id: default_pair_type.code_id,
+        commission_rate: default_pair_type.commission_rate,
+        protocol_fee_share: state.protocol_fee_share,
+        fee_collector: if let Some(fee_collector) = state.fee_collector {
+            Some(deps.api.addr_humanize(&fee_collector)?.to_string())
+        } else {
+            None
+        },
+        permissioned: state.permissioned,
+        creation_fee: if let Some(creation_fee) = state.creation_fee {
+            Some(creation_fee.to_normal(deps.api)?)
+        } else {
+            None
+        },
+        treasury: if let Some(treasury) = state.treasury {
+            Some(deps.api.addr_humanize(&treasury)?.to_string())
+        } else {
+            None
+        },
+        pair_types: state.pair_types.clone(),
     };
 
     Ok(resp)
 }
 
-pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_key = pair_key(&[
-        asset_infos[0].to_raw(deps.api)?,
-        asset_infos[1].to_raw(deps.api)?,
-    ]);
+pub fn query_asset_whitelist(
+    deps: Deps,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<AssetWhitelistResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(start_after.to_raw(deps.api)?)
+    } else {
+        None
+    };
+
+    let assets: Vec<AssetInfo> = read_asset_whitelist(deps.storage, deps.api, start_after, limit)?;
+    Ok(AssetWhitelistResponse { assets })
+}
+
+pub fn query_native_token_decimals(
+    deps: Deps,
+    denom: String,
+) -> StdResult<NativeTokenDecimalsResponse> {
+    match NATIVE_TOKEN_DECIMALS.may_load(deps.storage, denom.as_bytes())? {
+        Some(decimals) => Ok(NativeTokenDecimalsResponse { decimals }),
+        None => Err(StdError::generic_err(format!(
+            "Decimals of {} are not registered",
+            denom
+        ))),
+    }
+}
+
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<String>,
+) -> StdResult<PairInfo> {
+    let pair_key = pair_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        pair_type.as_deref().unwrap_or(DEFAULT_PAIR_TYPE),
+    );
     let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
     pair_info.to_normal(deps.api)
 }
 
-pub fn query_pairs(
+pub fn query_pair_by_address(deps: Deps, contract_addr: String) -> StdResult<PairInfo> {
+    let pair_key = PAIRS_BY_ADDRESS.load(
+        deps.storage,
+        deps.api.addr_canonicalize(&contract_addr)?.as_slice(),
+    )?;
+    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    pair_info.to_normal(deps.api)
+}
+
+pub fn query_pair_by_liquidity_token(deps: Deps, liquidity_token: String) -> StdResult<PairInfo> {
+    let pair_key = PAIRS_BY_LIQUIDITY_TOKEN.load(
+        deps.storage,
+        deps.api.addr_canonicalize(&liquidity_token)?.as_slice(),
+    )?;
+    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    pair_info.to_normal(deps.api)
+}
+
+/// Key of the pair to start after, the pair type defaults to xyk
+fn start_after_key(
     deps: Deps,
     start_after: Option<[AssetInfo; 2]>,
-    limit: Option<u32>,
-) -> StdResult<PairsResponse> {
+    start_after_pair_type: Option<String>,
+) -> StdResult<Option<Vec<u8>>> {
     let start_after = if let Some(start_after) = start_after {
-        Some([
-            start_after[0].to_raw(deps.api)?,
-            start_after[1].to_raw(deps.api)?,
-        ])
+        Some(pair_key(
+            &[
+                start_after[0].to_raw(deps.api)?,
+                start_after[1].to_raw(deps.api)?,
+            ],
+            start_after_pair_type
+                .as_deref()
+                .unwrap_or(DEFAULT_PAIR_TYPE),
+        ))
     } else {
         None
     };
 
+    Ok(start_after)
+}
+
+pub fn query_pairs(
+    deps: Deps,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let start_after = start_after_key(deps, start_after, start_after_pair_type)?;
     let pairs: Vec<PairInfo> = read_pairs(deps.storage, deps.api, start_after, limit)?;
     let resp = PairsResponse { pairs };
 
     Ok(resp)
 }
 
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let start_after = start_after_key(deps, start_after, start_after_pair_type)?;
+
+    let pairs: Vec<PairInfo> = read_pairs_by_asset(
+        deps.storage,
+        deps.api,
+        asset_info.to_raw(deps.api)?,
+        start_after,
+        limit,
+    )?;
+    Ok(PairsResponse { pairs })
+}
+
+fn assert_commission_rate(commission_rate: Decimal) -> StdResult<()> {
+    if commission_rate >= Decimal::one() {
+        return Err(StdError::generic_err("commission_rate must be less than 1"));
+    }
+
+    Ok(())
+}
+
+fn assert_protocol_fee_share(protocol_fee_share: Decimal) -> StdResult<()> {
+    if protocol_fee_share > Decimal::one() {
+        return Err(StdError::generic_err(
+            "protocol_fee_share cannot bigger than 1",
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // factories instantiated before the pair types were added hold a legacy config,
+    // its pair code becomes the default pair type
+    if CONFIG.load(deps.storage).is_err() {
+        let legacy_config: LegacyConfig = LEGACY_CONFIG.load(deps.storage)?;
+        let commission_rate = match legacy_config.commission_rate {
+            Some(commission_rate) => commission_rate,
+            None => Decimal::from_str(DEFAULT_COMMISSION_RATE)?,
+        };
+
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                owner: legacy_config.owner,
+                token_code_id: legacy_config.token_code_id,
+                pair_types: vec![PairTypeConfig {
+                    pair_type: DEFAULT_PAIR_TYPE.to_string(),
+                    code_id: legacy_config.pair_code_id,
+                    curve: PairType::Xyk,
+                    commission_rate,
+                }],
+                protocol_fee_share: legacy_config
+                    .protocol_fee_share
+                    .unwrap_or_else(Decimal::zero),
+                fee_collector: legacy_config.fee_collector,
+                permissioned: legacy_config.permissioned,
+                creation_fee: legacy_config.creation_fee,
+                treasury: legacy_config.treasury,
+            },
+        )?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "migrate"), ("version", CONTRACT_VERSION)]))
 }
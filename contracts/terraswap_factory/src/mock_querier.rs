@@ -1,11 +1,14 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Api, Coin, ContractResult, Empty, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, WasmQuery,
+    from_binary, from_slice, to_binary, Api, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
 use std::collections::HashMap;
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 use terraswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use terraswap::pair::QueryMsg as PairQueryMsg;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -23,8 +26,10 @@ pub fn mock_dependencies(
 }
 
 pub struct WasmMockQuerier {
-    base: MockQuerier<Empty>,
+    base: MockQuerier<TerraQueryWrapper>,
     terraswap_pair_querier: TerraswapPairQuerier,
+    token_querier: TokenQuerier,
+    tax_querier: TaxQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -48,10 +53,50 @@ pub(crate) fn pairs_to_map(pairs: &[(&String, &PairInfo)]) -> HashMap<String, Pa
     pairs_map
 }
 
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    decimals: HashMap<String, u8>,
+}
+
+impl TokenQuerier {
+    pub fn new(decimals: &[(&String, &u8)]) -> Self {
+        let mut decimals_map: HashMap<String, u8> = HashMap::new();
+        for (contract_addr, decimals) in decimals.iter() {
+            decimals_map.insert(contract_addr.to_string(), **decimals);
+        }
+        TokenQuerier {
+            decimals: decimals_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    caps: HashMap<String, Uint128>,
+}
+
+impl TaxQuerier {
+    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
+        TaxQuerier {
+            rate,
+            caps: caps_to_map(caps),
+        }
+    }
+}
+
+pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
+    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
+    for (denom, cap) in caps.iter() {
+        owner_map.insert(denom.to_string(), **cap);
+    }
+    owner_map
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
-        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
             Ok(v) => v,
             Err(e) => {
                 return SystemResult::Err(SystemError::InvalidRequest {
@@ -65,8 +110,66 @@ impl Querier for WasmMockQuerier {
 }
 
 impl WasmMockQuerier {
-    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
         match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Treasury {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
+                Ok(PairQueryMsg::Pair {}) => {
+                    match self.terraswap_pair_querier.pairs.get(contract_addr) {
+                        Some(v) => SystemResult::Ok(ContractResult::from(to_binary(v))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: format!("PairInfo is not found for {}", contract_addr),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                _ => match from_binary(msg).unwrap() {
+                    Cw20QueryMsg::TokenInfo {} => {
+                        match self.token_querier.decimals.get(contract_addr) {
+                            Some(decimals) => SystemResult::Ok(ContractResult::from(to_binary(
+                                &TokenInfoResponse {
+                                    name: "mAAPL".to_string(),
+                                    symbol: "mAAPL".to_string(),
+                                    decimals: *decimals,
+                                    total_supply: Uint128::zero(),
+                                },
+                            ))),
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: format!(
+                                    "No token info exists for the contract {}",
+                                    contract_addr
+                                ),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                },
+            },
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
                 let key: &[u8] = key.as_slice();
                 let prefix_pair_info = to_length_prefixed(b"pair_info").to_vec();
@@ -99,6 +202,9 @@ impl WasmMockQuerier {
                                 denom: "uusd".to_string(),
                             },
                         ],
+                        paused: false,
+                        pair_type: "xyk".to_string(),
+                        asset_decimals: [6, 6],
                     })))
                 } else {
                     panic!("DO NOT ENTER HERE")
@@ -110,10 +216,12 @@ impl WasmMockQuerier {
 }
 
 impl WasmMockQuerier {
-    pub fn new(base: MockQuerier<Empty>) -> Self {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
         WasmMockQuerier {
             base,
             terraswap_pair_querier: TerraswapPairQuerier::default(),
+            token_querier: TokenQuerier::default(),
+            tax_querier: TaxQuerier::default(),
         }
     }
 
@@ -122,6 +230,16 @@ impl WasmMockQuerier {
         self.terraswap_pair_querier = TerraswapPairQuerier::new(pairs);
     }
 
+    // configure the decimals of the tokens
+    pub fn with_token_decimals(&mut self, decimals: &[(&String, &u8)]) {
+        self.token_querier = TokenQuerier::new(decimals);
+    }
+
+    // configure the tax rate and caps of native tokens
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        self.tax_querier = TaxQuerier::new(rate, caps);
+    }
+
     // pub fn with_balance(&mut self, balances: &[(&HumanAddr, &[Coin])]) {
     //     for (addr, balance) in balances {
     //         self.base.update_balance(addr, balance.to_vec());
@@ -1,5 +1,6 @@
-use cosmwasm_std::{Addr, Binary, Deps, QueryRequest, StdResult, WasmQuery};
-use terraswap::asset::PairInfoRaw;
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, QueryRequest, StdResult, WasmQuery};
+use terraswap::asset::{PairInfo, PairInfoRaw};
+use terraswap::pair::QueryMsg as PairQueryMsg;
 
 pub fn query_liquidity_token(deps: Deps, contract_addr: Addr) -> StdResult<Addr> {
     // load pair_info form the pair contract
@@ -10,3 +11,10 @@ pub fn query_liquidity_token(deps: Deps, contract_addr: Addr) -> StdResult<Addr>
 
     deps.api.addr_humanize(&pair_info.liquidity_token)
 }
+
+pub fn query_pair_contract_info(deps: Deps, contract_addr: Addr) -> StdResult<PairInfo> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&PairQueryMsg::Pair {})?,
+    }))
+}
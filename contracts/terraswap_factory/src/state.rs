@@ -1,47 +1,165 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Api, CanonicalAddr, Order, StdResult, Storage};
-use cw_storage_plus::{Bound, Item, Map};
-use terraswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use cosmwasm_std::{Api, CanonicalAddr, Decimal, Empty, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map, U64Key};
+use terraswap::asset::{AssetInfo, AssetInfoRaw, AssetRaw, PairInfo, PairInfoRaw};
+use terraswap::factory::PairTypeConfig;
+use terraswap::ownership::OwnershipProposal;
+use terraswap::pair::DEFAULT_PAIR_TYPE;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
-    pub pair_code_id: u64,
     pub token_code_id: u64,
+    /// Code and default parameters of the pairs by pair type name
+    pub pair_types: Vec<PairTypeConfig>,
+    pub protocol_fee_share: Decimal,
+    pub fee_collector: Option<CanonicalAddr>,
+    /// Only whitelisted assets can be paired when set
+    #[serde(default)]
+    pub permissioned: bool,
+    /// Fee charged for each pair creation
+    #[serde(default)]
+    pub creation_fee: Option<AssetRaw>,
+    #[serde(default)]
+    pub treasury: Option<CanonicalAddr>,
 }
 
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 
+/// Pending transfer of the factory ownership
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
+/// Config layouts before the pair types were added,
+/// the oldest one has no commission rate nor protocol fee
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub pair_code_id: u64,
+    pub token_code_id: u64,
+    #[serde(default)]
+    pub commission_rate: Option<Decimal>,
+    #[serde(default)]
+    pub protocol_fee_share: Option<Decimal>,
+    #[serde(default)]
+    pub fee_collector: Option<CanonicalAddr>,
+    #[serde(default)]
+    pub permissioned: bool,
+    #[serde(default)]
+    pub creation_fee: Option<AssetRaw>,
+    #[serde(default)]
+    pub treasury: Option<CanonicalAddr>,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("\u{0}\u{6}config");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
     pub asset_infos: [AssetInfoRaw; 2],
+    pub pair_type: String,
+    pub asset_decimals: [u8; 2],
+    /// Liquidity provided once the pair is instantiated
+    #[serde(default)]
+    pub initial_liquidity: Option<InitialLiquidity>,
 }
 
-pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitialLiquidity {
+    pub assets: [AssetRaw; 2],
+    pub slippage_tolerance: Option<Decimal>,
+    /// Owner of the assets, the native ones are already held by the factory
+    pub provider: CanonicalAddr,
+    pub receiver: CanonicalAddr,
+}
+
+/// Id of the last pair creation, each creation replies with its own id
+pub const PAIR_CREATION_ID: Item<u64> = Item::new("pair_creation_id");
+/// Pairs waiting for the reply of their instantiation, keyed by the reply id
+pub const TMP_PAIR_INFOS: Map<U64Key, TmpPairInfo> = Map::new("tmp_pair_infos");
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pair_info");
+/// Reverse index from each asset to the keys of the pairs containing it
+pub const PAIRS_BY_ASSET: Map<(&[u8], &[u8]), Empty> = Map::new("pairs_by_asset");
+/// Pair keys by the pair contract address
+pub const PAIRS_BY_ADDRESS: Map<&[u8], Vec<u8>> = Map::new("pairs_by_address");
+/// Pair keys by the liquidity token address
+pub const PAIRS_BY_LIQUIDITY_TOKEN: Map<&[u8], Vec<u8>> = Map::new("pairs_by_liquidity_token");
+
+/// Assets allowed in new pairs while the creation is permissioned
+pub const ASSET_WHITELIST: Map<&[u8], AssetInfoRaw> = Map::new("asset_whitelist");
+/// Assets never allowed in new pairs
+pub const ASSET_BLACKLIST: Map<&[u8], AssetInfoRaw> = Map::new("asset_blacklist");
+/// Decimals of the native tokens by denom
+pub const NATIVE_TOKEN_DECIMALS: Map<&[u8], u8> = Map::new("native_token_decimals");
+
+/// Saves the pair and its index entries
+pub fn save_pair(storage: &mut dyn Storage, pair_info: &PairInfoRaw) -> StdResult<()> {
+    let pair_key = pair_key(&pair_info.asset_infos, &pair_info.pair_type);
+    PAIRS.save(storage, &pair_key, pair_info)?;
+    index_pair(storage, &pair_key, pair_info)
+}
+
+pub fn index_pair(
+    storage: &mut dyn Storage,
+    pair_key: &[u8],
+    pair_info: &PairInfoRaw,
+) -> StdResult<()> {
+    for asset_info in pair_info.asset_infos.iter() {
+        PAIRS_BY_ASSET.save(storage, (asset_info.as_bytes(), pair_key), &Empty {})?;
+    }
+    PAIRS_BY_ADDRESS.save(
+        storage,
+        pair_info.contract_addr.as_slice(),
+        &pair_key.to_vec(),
+    )?;
+    PAIRS_BY_LIQUIDITY_TOKEN.save(
+        storage,
+        pair_info.liquidity_token.as_slice(),
+        &pair_key.to_vec(),
+    )?;
+
+    Ok(())
+}
 
-pub fn pair_key(asset_infos: &[AssetInfoRaw; 2]) -> Vec<u8> {
+/// Removes the pair and its index entries
+pub fn remove_pair(storage: &mut dyn Storage, pair_info: &PairInfoRaw) {
+    let pair_key = pair_key(&pair_info.asset_infos, &pair_info.pair_type);
+    PAIRS.remove(storage, &pair_key);
+    for asset_info in pair_info.asset_infos.iter() {
+        PAIRS_BY_ASSET.remove(storage, (asset_info.as_bytes(), &pair_key));
+    }
+    PAIRS_BY_ADDRESS.remove(storage, pair_info.contract_addr.as_slice());
+    PAIRS_BY_LIQUIDITY_TOKEN.remove(storage, pair_info.liquidity_token.as_slice());
+}
+
+/// The pairs of the default pair type keep the legacy key,
+/// the other types are appended to it with their length as prefix
+pub fn pair_key(asset_infos: &[AssetInfoRaw; 2], pair_type: &str) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
 
-    [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
+    let mut key = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat();
+    if pair_type != DEFAULT_PAIR_TYPE {
+        key.extend_from_slice(&(pair_type.len() as u16).to_be_bytes());
+        key.extend_from_slice(pair_type.as_bytes());
+    }
+    key
 }
 
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+/// Reads the pairs after the pair key `start_after`
 pub fn read_pairs(
     storage: &dyn Storage,
     api: &dyn Api,
-    start_after: Option<[AssetInfoRaw; 2]>,
+    start_after: Option<Vec<u8>>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PairInfo>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after).map(Bound::exclusive);
+    let start = start_after.map(Bound::exclusive);
 
     PAIRS
         .range(storage, start, None, Order::Ascending)
@@ -53,19 +171,90 @@ pub fn read_pairs(
         .collect::<StdResult<Vec<PairInfo>>>()
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<[AssetInfoRaw; 2]>) -> Option<Vec<u8>> {
-    start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+/// Reads the pairs of `pair_type` after the pair key `start_after`,
+/// the pairs of the other types are skipped without counting to the limit
+pub fn read_pairs_of_type(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    pair_type: &str,
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PairInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    PAIRS
+        .range(storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, v)) => v.pair_type == pair_type,
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            v.to_normal(api)
+        })
+        .collect::<StdResult<Vec<PairInfo>>>()
+}
+
+/// Indexes the pairs after the pair key `start_after`, returns the number of indexed pairs
+pub fn index_pairs(
+    storage: &mut dyn Storage,
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> StdResult<usize> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let pairs = PAIRS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Vec<u8>, PairInfoRaw)>>>()?;
+    for (pair_key, pair_info) in pairs.iter() {
+        index_pair(storage, pair_key, pair_info)?;
+    }
 
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-            .concat()
-            .as_slice()
-            .to_vec();
-        v.push(1);
-        v
-    })
+    Ok(pairs.len())
+}
+
+pub fn read_pairs_by_asset(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    asset_info: AssetInfoRaw,
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PairInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    PAIRS_BY_ASSET
+        .prefix(asset_info.as_bytes())
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (pair_key, _) = item?;
+            PAIRS.load(storage, &pair_key)?.to_normal(api)
+        })
+        .collect::<StdResult<Vec<PairInfo>>>()
+}
+
+pub fn read_asset_whitelist(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    start_after: Option<AssetInfoRaw>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AssetInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|asset_info| Bound::exclusive(asset_info.as_bytes()));
+
+    ASSET_WHITELIST
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            v.to_normal(api)
+        })
+        .collect::<StdResult<Vec<AssetInfo>>>()
 }
 
 #[cfg(test)]
@@ -93,8 +282,13 @@ mod test {
             &mut deps.storage,
             &Config {
                 owner: deps.api.addr_canonicalize("owner0000").unwrap(),
-                pair_code_id: 1,
                 token_code_id: 1,
+                pair_types: vec![],
+                protocol_fee_share: Decimal::zero(),
+                fee_collector: None,
+                permissioned: false,
+                creation_fee: None,
+                treasury: None,
             },
         )
         .unwrap();
@@ -135,7 +329,15 @@ mod test {
     ) -> StdResult<Vec<PairInfo>> {
         let pair_bucket: ReadonlyBucket<PairInfoRaw> = bucket_read(storage, PREFIX_PAIR_INFO);
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-        let start = calc_range_start(start_after);
+        // this will set the first key after the provided key, by appending a 1 byte
+        let start = start_after.map(|asset_infos| {
+            let mut asset_infos = asset_infos.to_vec();
+            asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+            let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat();
+            v.push(1);
+            v
+        });
         pair_bucket
             .range(start.as_deref(), None, Order::Ascending)
             .take(limit)
@@ -160,6 +362,9 @@ mod test {
             ],
             contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
             liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            paused: false,
+            pair_type: DEFAULT_PAIR_TYPE.to_string(),
+            asset_decimals: [6, 6],
         };
 
         let pair_info2 = PairInfoRaw {
@@ -173,6 +378,9 @@ mod test {
             ],
             contract_addr: deps.api.addr_canonicalize("pair0001").unwrap(),
             liquidity_token: deps.api.addr_canonicalize("liquidity0001").unwrap(),
+            paused: false,
+            pair_type: DEFAULT_PAIR_TYPE.to_string(),
+            asset_decimals: [6, 6],
         };
 
         store_pair(&mut deps.storage, &pair_info).unwrap();
@@ -180,14 +388,20 @@ mod test {
 
         assert_eq!(
             PAIRS
-                .load(&deps.storage, &pair_key(&pair_info.asset_infos))
+                .load(
+                    &deps.storage,
+                    &pair_key(&pair_info.asset_infos, DEFAULT_PAIR_TYPE)
+                )
                 .unwrap(),
             read_pair(&deps.storage, &pair_info.asset_infos).unwrap()
         );
 
         assert_eq!(
             PAIRS
-                .load(&deps.storage, &pair_key(&pair_info2.asset_infos))
+                .load(
+                    &deps.storage,
+                    &pair_key(&pair_info2.asset_infos, DEFAULT_PAIR_TYPE)
+                )
                 .unwrap(),
             read_pair(&deps.storage, &pair_info2.asset_infos).unwrap()
         );
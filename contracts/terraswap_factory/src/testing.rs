@@ -1,16 +1,28 @@
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use crate::mock_querier::mock_dependencies;
 
-use crate::state::{pair_key, TmpPairInfo, TMP_PAIR_INFO};
+use crate::state::{
+    pair_key, save_pair, LegacyConfig, TmpPairInfo, CONFIG, LEGACY_CONFIG, PAIRS, TMP_PAIR_INFOS,
+};
 
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, ContractResult, Reply, ReplyOn, StdError, SubMsg,
-    SubMsgExecutionResponse, WasmMsg,
+    attr, from_binary, to_binary, Api, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Deps,
+    DepsMut, Reply, ReplyOn, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::U64Key;
+use terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
+use terraswap::factory::{
+    AssetWhitelistResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    NativeTokenDecimalsResponse, PairTypeConfig, PairsResponse, QueryMsg,
+};
+use terraswap::ownership::MAX_PROPOSAL_TTL;
+use terraswap::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    MigrateMsg as PairMigrateMsg, PairType,
 };
-use terraswap::asset::{AssetInfo, PairInfo};
-use terraswap::factory::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use terraswap::pair::InstantiateMsg as PairInstantiateMsg;
 
 #[test]
 fn proper_initialization() {
@@ -19,6 +31,9 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -30,6 +45,9 @@ fn proper_initialization() {
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
     assert_eq!(321u64, config_res.pair_code_id);
+    assert_eq!(Decimal::permille(3), config_res.commission_rate);
+    assert_eq!(Decimal::zero(), config_res.protocol_fee_share);
+    assert_eq!(None, config_res.fee_collector);
     assert_eq!("addr0000".to_string(), config_res.owner);
 }
 
@@ -40,6 +58,9 @@ fn update_config() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -47,31 +68,16 @@ fn update_config() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
-    let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("addr0001".to_string()),
-        pair_code_id: None,
-        token_code_id: None,
-    };
-
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
-
-    // it worked, let's query the state
-    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
-    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
-    assert_eq!(123u64, config_res.token_code_id);
-    assert_eq!(321u64, config_res.pair_code_id);
-    assert_eq!("addr0001".to_string(), config_res.owner);
-
     // update left items
     let env = mock_env();
-    let info = mock_info("addr0001", &[]);
+    let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        pair_code_id: Some(100u64),
         token_code_id: Some(200u64),
+        protocol_fee_share: Some(Decimal::percent(10)),
+        fee_collector: Some("collector0000".to_string()),
+        permissioned: Some(true),
+        creation_fee: None,
+        treasury: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -81,16 +87,23 @@ fn update_config() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(200u64, config_res.token_code_id);
-    assert_eq!(100u64, config_res.pair_code_id);
-    assert_eq!("addr0001".to_string(), config_res.owner);
+    assert_eq!(321u64, config_res.pair_code_id);
+    assert_eq!(Decimal::permille(3), config_res.commission_rate);
+    assert_eq!(Decimal::percent(10), config_res.protocol_fee_share);
+    assert_eq!(Some("collector0000".to_string()), config_res.fee_collector);
+    assert!(config_res.permissioned);
+    assert_eq!("addr0000".to_string(), config_res.owner);
 
     // Unauthorized err
     let env = mock_env();
-    let info = mock_info("addr0000", &[]);
+    let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
-        pair_code_id: None,
         token_code_id: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+        permissioned: None,
+        creation_fee: None,
+        treasury: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg);
@@ -100,6 +113,165 @@ fn update_config() {
     }
 }
 
+#[test]
+fn ownership_transfer() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+        expires_in: 100,
+    };
+
+    // only the owner can propose
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ProposeNewOwner {
+            owner: "addr0001".to_string(),
+            expires_in: MAX_PROPOSAL_TTL + 1,
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            format!("expires_in cannot be greater than {}", MAX_PROPOSAL_TTL)
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("new_owner", "addr0001"),
+            attr("expires_at", (env.block.time.seconds() + 100).to_string()),
+        ]
+    );
+
+    // the owner is unchanged until the proposal is claimed
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!("addr0000".to_string(), config_res.owner);
+
+    // only the proposed owner can claim
+    let info = mock_info("addr0002", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // expired proposals cannot be claimed
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(101);
+    let info = mock_info("addr0001", &[]);
+    match execute(
+        deps.as_mut(),
+        env,
+        info.clone(),
+        ExecuteMsg::ClaimOwnership {},
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "ownership proposal expired"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_ownership"),
+            attr("new_owner", "addr0001"),
+        ]
+    );
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!("addr0001".to_string(), config_res.owner);
+
+    // the proposal is consumed
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "ownership proposal not found"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // a dropped proposal cannot be claimed
+    let info = mock_info("addr0001", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ProposeNewOwner {
+            owner: "addr0002".to_string(),
+            expires_in: 100,
+        },
+    )
+    .unwrap();
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::DropOwnershipProposal {},
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DropOwnershipProposal {},
+    )
+    .unwrap();
+
+    let info = mock_info("addr0002", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "ownership proposal not found"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
 #[test]
 fn create_pair() {
     let mut deps = mock_dependencies(&[]);
@@ -107,6 +279,9 @@ fn create_pair() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
     };
 
     let env = mock_env();
@@ -115,6 +290,11 @@ fn create_pair() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
+    deps.querier.with_token_decimals(&[
+        (&"asset0000".to_string(), &6u8),
+        (&"asset0001".to_string(), &8u8),
+    ]);
+
     let asset_infos = [
         AssetInfo::Token {
             contract_addr: "asset0000".to_string(),
@@ -126,6 +306,8 @@ fn create_pair() {
 
     let msg = ExecuteMsg::CreatePair {
         asset_infos: asset_infos.clone(),
+        pair_type: None,
+        amp: None,
     };
 
     let env = mock_env();
@@ -135,7 +317,8 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "asset0000-asset0001")
+            attr("pair", "asset0000-asset0001"),
+            attr("pair_type", "xyk")
         ]
     );
     assert_eq!(
@@ -148,12 +331,17 @@ fn create_pair() {
                 msg: to_binary(&PairInstantiateMsg {
                     asset_infos: asset_infos.clone(),
                     token_code_id: 123u64,
+                    commission_rate: Some(Decimal::permille(3)),
+                    pair_type: Some(PairType::Xyk),
+                    pair_type_name: Some("xyk".to_string()),
+                    amp: None,
+                    asset_decimals: [6, 8],
                 })
                 .unwrap(),
                 code_id: 321u64,
                 funds: vec![],
                 label: "".to_string(),
-                admin: None,
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
             }
             .into()
         },]
@@ -165,10 +353,13 @@ fn create_pair() {
     ];
 
     assert_eq!(
-        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TMP_PAIR_INFOS.load(&deps.storage, U64Key::new(1)).unwrap(),
         TmpPairInfo {
+            pair_type: "xyk".to_string(),
             asset_infos: raw_infos.clone(),
-            pair_key: pair_key(&raw_infos),
+            pair_key: pair_key(&raw_infos, "xyk"),
+            asset_decimals: [6, 8],
+            initial_liquidity: None,
         }
     );
 }
@@ -191,13 +382,17 @@ fn reply_test() {
         asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
     ];
 
-    let pair_key = pair_key(&raw_infos);
-    TMP_PAIR_INFO
+    let pair_key = pair_key(&raw_infos, "xyk");
+    TMP_PAIR_INFOS
         .save(
             &mut deps.storage,
+            U64Key::new(1),
             &TmpPairInfo {
+                pair_type: "xyk".to_string(),
                 asset_infos: raw_infos,
                 pair_key,
+                asset_decimals: [6, 8],
+                initial_liquidity: None,
             },
         )
         .unwrap();
@@ -224,16 +419,28 @@ fn reply_test() {
             ],
             contract_addr: "pair0000".to_string(),
             liquidity_token: "liquidity0000".to_string(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
         },
     )]);
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the pending pair is removed
+    assert_eq!(
+        TMP_PAIR_INFOS
+            .may_load(&deps.storage, U64Key::new(1))
+            .unwrap(),
+        None
+    );
+
     let query_res = query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -245,6 +452,2270 @@ fn reply_test() {
             liquidity_token: "liquidity0000".to_string(),
             contract_addr: "pair0000".to_string(),
             asset_infos,
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 8],
+        }
+    );
+
+    // the reply also indexes the pair by its addresses
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            contract_addr: "pair0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(pair_res, from_binary(&query_res).unwrap());
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLiquidityToken {
+            liquidity_token: "liquidity0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(pair_res, from_binary(&query_res).unwrap());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            contract_addr: "pair0001".to_string(),
+        },
+    );
+    match res {
+        Err(StdError::NotFound { .. }) => {}
+        _ => panic!("Must return not found error"),
+    }
+}
+
+#[test]
+fn update_pair_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+    ];
+
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    PAIRS
+        .save(
+            &mut deps.storage,
+            &pair_key(&raw_infos, "xyk"),
+            &PairInfoRaw {
+                asset_infos: raw_infos.clone(),
+                contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+                liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                paused: false,
+                pair_type: "xyk".to_string(),
+                asset_decimals: [6, 6],
+            },
+        )
+        .unwrap();
+
+    // Unauthorized err
+    let msg = ExecuteMsg::UpdatePairConfig {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        commission_rate: Some(Decimal::permille(5)),
+    };
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // invalid commission rate
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdatePairConfig {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+            commission_rate: Some(Decimal::one()),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "commission_rate must be less than 1")
         }
+        _ => panic!("Must return generic error"),
+    }
+
+    // forwarded to the pair contract
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::UpdateConfig {
+                commission_rate: Some(Decimal::permille(5)),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn ramp_pair_amp() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+    ];
+
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    PAIRS
+        .save(
+            &mut deps.storage,
+            &pair_key(&raw_infos, "xyk"),
+            &PairInfoRaw {
+                asset_infos: raw_infos.clone(),
+                contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+                liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                paused: false,
+                pair_type: "xyk".to_string(),
+                asset_decimals: [6, 6],
+            },
+        )
+        .unwrap();
+
+    // Unauthorized err
+    let msg = ExecuteMsg::RampPairAmp {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        future_amp: 200u64,
+        future_time: 1571900000u64,
+    };
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // forwarded to the pair contract
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::RampAmp {
+                future_amp: 200u64,
+                future_time: 1571900000u64,
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::StopPairAmpRamp {
+        asset_infos,
+        pair_type: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::StopRampAmp {}).unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn set_pair_status() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+    ];
+
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    PAIRS
+        .save(
+            &mut deps.storage,
+            &pair_key(&raw_infos, "xyk"),
+            &PairInfoRaw {
+                asset_infos: raw_infos.clone(),
+                contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+                liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                paused: false,
+                pair_type: "xyk".to_string(),
+                asset_decimals: [6, 6],
+            },
+        )
+        .unwrap();
+
+    // Unauthorized err
+    let msg = ExecuteMsg::SetPairStatus {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        paused: true,
+    };
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // forwarded to the pair contract
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&PairExecuteMsg::SetStatus { paused: true }).unwrap(),
+        }))]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert!(pair_res.paused);
+
+    // resume the pair
+    let msg = ExecuteMsg::SetPairStatus {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        paused: false,
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert!(!pair_res.paused);
+}
+
+#[test]
+fn migrate_pairs() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+    ];
+    let asset_infos_2 = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+
+    for (asset_infos, pair, pair_type) in [
+        (&asset_infos, "pair0000", "xyk"),
+        (&asset_infos_2, "pair0001", "xyk"),
+        (&asset_infos, "pair0002", "stable"),
+    ] {
+        let raw_infos = [
+            asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+            asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+        ];
+
+        PAIRS
+            .save(
+                &mut deps.storage,
+                &pair_key(&raw_infos, pair_type),
+                &PairInfoRaw {
+                    asset_infos: raw_infos.clone(),
+                    contract_addr: deps.api.addr_canonicalize(pair).unwrap(),
+                    liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                    paused: false,
+                    pair_type: pair_type.to_string(),
+                    asset_decimals: [6, 6],
+                },
+            )
+            .unwrap();
+    }
+
+    // Unauthorized err
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 456u64,
+        pair_type: None,
+        start_after: None,
+        start_after_pair_type: None,
+        limit: Some(1),
+    };
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the first page migrates a single pair
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: "pair0000".to_string(),
+            new_code_id: 456u64,
+            msg: to_binary(&PairMigrateMsg {
+                factory: Some(MOCK_CONTRACT_ADDR.to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the next page starts after the migrated pair and skips the stable pair
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 456u64,
+        pair_type: None,
+        start_after: Some(asset_infos.clone()),
+        start_after_pair_type: None,
+        limit: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: "pair0001".to_string(),
+            new_code_id: 456u64,
+            msg: to_binary(&PairMigrateMsg {
+                factory: Some(MOCK_CONTRACT_ADDR.to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("pair_type", "xyk"),
+            attr("code_id", "456"),
+            attr("migrated_count", "1"),
+            attr("last_pair", "uusd-uluna"),
+        ]
+    );
+
+    // the stable pairs are migrated to their own code,
+    // the xyk pair before the stable pair does not take the page
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 789u64,
+        pair_type: Some("stable".to_string()),
+        start_after: None,
+        start_after_pair_type: None,
+        limit: Some(1),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: "pair0002".to_string(),
+            new_code_id: 789u64,
+            msg: to_binary(&PairMigrateMsg {
+                factory: Some(MOCK_CONTRACT_ADDR.to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("pair_type", "stable"),
+            attr("code_id", "789"),
+            attr("migrated_count", "1"),
+            attr("last_pair", "uusd-ukrw"),
+        ]
+    );
+
+    // nothing is left after the last pair of the type
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 789u64,
+        pair_type: Some("stable".to_string()),
+        start_after: Some(asset_infos),
+        start_after_pair_type: Some("stable".to_string()),
+        limit: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("pair_type", "stable"),
+            attr("code_id", "789"),
+            attr("migrated_count", "0"),
+        ]
+    );
+}
+
+#[test]
+fn migrate_legacy_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    // factory instantiated before the version and the fee config were stored
+    LEGACY_CONFIG
+        .save(
+            &mut deps.storage,
+            &LegacyConfig {
+                owner: deps.api.addr_canonicalize("addr0000").unwrap(),
+                pair_code_id: 321u64,
+                token_code_id: 123u64,
+                commission_rate: None,
+                protocol_fee_share: None,
+                fee_collector: None,
+                permissioned: false,
+                creation_fee: None,
+                treasury: None,
+            },
+        )
+        .unwrap();
+    assert!(CONFIG.load(&deps.storage).is_err());
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "migrate"));
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        config_res,
+        ConfigResponse {
+            owner: "addr0000".to_string(),
+            pair_code_id: 321u64,
+            token_code_id: 123u64,
+            commission_rate: Decimal::permille(3),
+            protocol_fee_share: Decimal::zero(),
+            fee_collector: None,
+            permissioned: false,
+            creation_fee: None,
+            treasury: None,
+            pair_types: vec![PairTypeConfig {
+                pair_type: "xyk".to_string(),
+                code_id: 321u64,
+                curve: PairType::Xyk,
+                commission_rate: Decimal::permille(3),
+            }],
+        }
+    );
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:terraswap-factory");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // migrating again keeps the config
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config_res, from_binary(&query_res).unwrap());
+
+    // downgrades are refused
+    set_contract_version(&mut deps.storage, "crates.io:terraswap-factory", "99.0.0").unwrap();
+    match migrate(deps.as_mut(), mock_env(), MigrateMsg {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            format!(
+                "cannot migrate from 99.0.0 to the older version {}",
+                env!("CARGO_PKG_VERSION")
+            )
+        ),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+fn native_token(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+fn add_native_token_decimals(deps: DepsMut, denom: &str, decimals: u8) {
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: denom.to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    let msg = ExecuteMsg::AddNativeTokenDecimals {
+        denom: denom.to_string(),
+        decimals,
+    };
+    execute(deps, mock_env(), info, msg).unwrap();
+}
+
+fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+) -> Vec<String> {
+    let res: PairsResponse = from_binary(
+        &query(
+            deps,
+            mock_env(),
+            QueryMsg::PairsByAsset {
+                asset_info,
+                start_after,
+                start_after_pair_type: None,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.pairs
+        .into_iter()
+        .map(|pair| pair.contract_addr)
+        .collect()
+}
+
+#[test]
+fn pairs_by_asset() {
+    let mut deps = mock_dependencies(&[]);
+
+    let pairs = [
+        ([native_token("uusd"), native_token("ukrw")], "pair0000"),
+        ([native_token("uusd"), native_token("uluna")], "pair0001"),
+        ([native_token("ukrw"), native_token("uluna")], "pair0002"),
+    ];
+    let pair_infos: Vec<PairInfoRaw> = pairs
+        .iter()
+        .map(|(asset_infos, pair)| PairInfoRaw {
+            asset_infos: [
+                asset_infos[0].to_raw(&deps.api).unwrap(),
+                asset_infos[1].to_raw(&deps.api).unwrap(),
+            ],
+            contract_addr: deps.api.addr_canonicalize(pair).unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
+        })
+        .collect();
+
+    // the first two pairs are registered before the reverse index existed
+    for pair_info in pair_infos[..2].iter() {
+        PAIRS
+            .save(
+                &mut deps.storage,
+                &pair_key(&pair_info.asset_infos, "xyk"),
+                pair_info,
+            )
+            .unwrap();
+    }
+
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("uusd"), None, None),
+        Vec::<String>::new()
+    );
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the owner indexes the pairs registered before the index page by page
+    let msg = ExecuteMsg::IndexPairs {
+        start_after: None,
+        start_after_pair_type: None,
+        limit: Some(1),
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "index_pairs"), attr("indexed_count", "1")]
+    );
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("uusd"), None, None),
+        vec!["pair0000".to_string()]
+    );
+
+    let msg = ExecuteMsg::IndexPairs {
+        start_after: Some(pairs[0].0.clone()),
+        start_after_pair_type: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "index_pairs"), attr("indexed_count", "1")]
+    );
+
+    // new pairs are indexed when they are saved
+    save_pair(&mut deps.storage, &pair_infos[2]).unwrap();
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            contract_addr: "pair0000".to_string(),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.asset_infos, pairs[0].0);
+
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("uusd"), None, None),
+        vec!["pair0000".to_string(), "pair0001".to_string()]
+    );
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("uluna"), None, None),
+        vec!["pair0002".to_string(), "pair0001".to_string()]
+    );
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("uaud"), None, None),
+        Vec::<String>::new()
+    );
+
+    // pagination
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), native_token("ukrw"), None, Some(1)),
+        vec!["pair0002".to_string()]
+    );
+    assert_eq!(
+        query_pairs_by_asset(
+            deps.as_ref(),
+            native_token("ukrw"),
+            Some([native_token("uluna"), native_token("ukrw")]),
+            None
+        ),
+        vec!["pair0000".to_string()]
+    );
+}
+
+#[test]
+fn create_multiple_pairs() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+    add_native_token_decimals(deps.as_mut(), "ukrw", 6);
+    add_native_token_decimals(deps.as_mut(), "uluna", 6);
+
+    let pairs = [
+        (
+            [native_token("uusd"), native_token("ukrw")],
+            "pair0000",
+            "liquidity0000",
+        ),
+        (
+            [native_token("uusd"), native_token("uluna")],
+            "pair0001",
+            "liquidity0001",
+        ),
+        (
+            [native_token("ukrw"), native_token("uluna")],
+            "pair0002",
+            "liquidity0002",
+        ),
+    ];
+
+    // every creation in the batch replies with its own id
+    for (i, (asset_infos, _, _)) in pairs.iter().enumerate() {
+        let msg = ExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+            amp: None,
+        };
+        let info = mock_info("addr0000", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages[0].id, i as u64 + 1);
+    }
+
+    // the same pair can't be created twice in the batch
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: pairs[0].0.clone(),
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages[0].id, 4);
+
+    let pair_infos: Vec<(String, PairInfo)> = pairs
+        .iter()
+        .map(|(asset_infos, pair, liquidity_token)| {
+            (
+                pair.to_string(),
+                PairInfo {
+                    asset_infos: asset_infos.clone(),
+                    contract_addr: pair.to_string(),
+                    liquidity_token: liquidity_token.to_string(),
+                    paused: false,
+                    pair_type: "xyk".to_string(),
+                    asset_decimals: [6, 6],
+                },
+            )
+        })
+        .collect();
+    deps.querier.with_terraswap_pairs(
+        &pair_infos
+            .iter()
+            .map(|(pair, pair_info)| (pair, pair_info))
+            .collect::<Vec<(&String, &PairInfo)>>(),
+    );
+
+    // replies arrive in any order
+    for id in [3u64, 1u64, 2u64] {
+        let pair = pairs[id as usize - 1].1;
+        let mut data = vec![10, pair.len() as u8];
+        data.extend_from_slice(pair.as_bytes());
+        let reply_msg = Reply {
+            id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(data.into()),
+            }),
+        };
+
+        let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        assert_eq!(res.attributes[0], attr("pair_contract_addr", pair));
+        assert_eq!(
+            TMP_PAIR_INFOS
+                .may_load(&deps.storage, U64Key::new(id))
+                .unwrap(),
+            None
+        );
+    }
+
+    for (asset_infos, pair, liquidity_token) in pairs.iter() {
+        let query_res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap();
+        let pair_res: PairInfo = from_binary(&query_res).unwrap();
+        assert_eq!(
+            pair_res,
+            PairInfo {
+                asset_infos: asset_infos.clone(),
+                contract_addr: pair.to_string(),
+                liquidity_token: liquidity_token.to_string(),
+                paused: false,
+                pair_type: "xyk".to_string(),
+                asset_decimals: [6, 6],
+            }
+        );
+    }
+
+    // the duplicated creation fails, reverting the whole batch
+    let reply_msg = Reply {
+        id: 4,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(vec![10, 8, 112, 97, 105, 114, 48, 48, 48, 48].into()),
+        }),
+    };
+    match reply(deps.as_mut(), mock_env(), reply_msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Pair already exists"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn create_pair_with_liquidity() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+    deps.querier
+        .with_token_decimals(&[(&"asset0000".to_string(), &6u8)]);
+
+    let assets = [
+        Asset {
+            info: native_token("uusd"),
+            amount: Uint128::from(1_000_000u128),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(2_000_000u128),
+        },
+    ];
+    let msg = ExecuteMsg::CreatePairWithLiquidity {
+        assets: assets.clone(),
+        slippage_tolerance: None,
+        receiver: None,
+        pair_type: None,
+        amp: None,
+    };
+
+    // the native assets must be sent
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Native token balance mismatch between the argument and the transferred"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages[0].id, 1);
+    assert_eq!(
+        TMP_PAIR_INFOS
+            .load(&deps.storage, U64Key::new(1))
+            .unwrap()
+            .initial_liquidity
+            .unwrap()
+            .receiver,
+        deps.api.addr_canonicalize("addr0000").unwrap()
+    );
+
+    deps.querier.with_terraswap_pairs(&[(
+        &"pair0000".to_string(),
+        &PairInfo {
+            asset_infos: [assets[0].info.clone(), assets[1].info.clone()],
+            contract_addr: "pair0000".to_string(),
+            liquidity_token: "liquidity0000".to_string(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
+        },
+    )]);
+
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(vec![10, 8, 112, 97, 105, 114, 48, 48, 48, 48].into()),
+        }),
+    };
+
+    // the reply pulls the tokens and provides the liquidity to the new pair
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(2_000_000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: "pair0000".to_string(),
+                    amount: Uint128::from(2_000_000u128),
+                    expires: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(999_000u128),
+                }],
+                msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                    assets: [
+                        Asset {
+                            info: native_token("uusd"),
+                            amount: Uint128::from(999_000u128),
+                        },
+                        assets[1].clone(),
+                    ],
+                    slippage_tolerance: None,
+                    receiver: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the pair type and the amplification are forwarded to the pair
+    let info = mock_info("addr0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdatePairType {
+            pair_type: "stable".to_string(),
+            code_id: 654u64,
+            curve: PairType::Stable,
+            commission_rate: None,
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::CreatePairWithLiquidity {
+        assets: assets.clone(),
+        slippage_tolerance: None,
+        receiver: None,
+        pair_type: Some("stable".to_string()),
+        amp: Some(100u64),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, msg, .. }) => {
+            assert_eq!(*code_id, 654u64);
+            let pair_msg: PairInstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(pair_msg.pair_type, Some(PairType::Stable));
+            assert_eq!(pair_msg.pair_type_name, Some("stable".to_string()));
+            assert_eq!(pair_msg.amp, Some(100u64));
+        }
+        _ => panic!("Must instantiate the pair"),
+    }
+    assert!(TMP_PAIR_INFOS
+        .load(&deps.storage, U64Key::new(2))
+        .unwrap()
+        .initial_liquidity
+        .is_some());
+}
+
+#[test]
+fn permissioned_create_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+    add_native_token_decimals(deps.as_mut(), "ukrw", 6);
+    add_native_token_decimals(deps.as_mut(), "uluna", 6);
+    add_native_token_decimals(deps.as_mut(), "uscam", 6);
+
+    // blacklisted assets are refused even while the creation is open
+    let msg = ExecuteMsg::UpdateAssetBlacklist {
+        add: vec![native_token("uscam")],
+        remove: vec![],
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_asset_blacklist"),
+            attr("added", "uscam"),
+            attr("removed", ""),
+        ]
+    );
+
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [native_token("uusd"), native_token("uscam")],
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset uscam is blacklisted"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // switch to the permissioned creation
+    let msg = ExecuteMsg::UpdateConfig {
+        token_code_id: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+        permissioned: Some(true),
+        creation_fee: None,
+        treasury: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAssetWhitelist {
+        add: vec![
+            native_token("uusd"),
+            native_token("uluna"),
+            native_token("ukrw"),
+        ],
+        remove: vec![native_token("ukrw")],
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_asset_whitelist"),
+            attr("added", "uusd,uluna,ukrw"),
+            attr("removed", "ukrw"),
+        ]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetWhitelist {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: AssetWhitelistResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        whitelist_res.assets,
+        vec![native_token("uluna"), native_token("uusd")]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetWhitelist {
+            start_after: Some(native_token("uluna")),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let whitelist_res: AssetWhitelistResponse = from_binary(&query_res).unwrap();
+    assert_eq!(whitelist_res.assets, vec![native_token("uusd")]);
+
+    // assets off the whitelist are refused
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [native_token("uusd"), native_token("ukrw")],
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset ukrw is not whitelisted"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // anyone can create a pair of whitelisted assets
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [native_token("uusd"), native_token("uluna")],
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn creation_fee() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+    add_native_token_decimals(deps.as_mut(), "ukrw", 6);
+    add_native_token_decimals(deps.as_mut(), "uluna", 6);
+    deps.querier
+        .with_token_decimals(&[(&"asset0000".to_string(), &6u8)]);
+
+    let update_fee = |creation_fee: Asset, treasury: Option<String>| ExecuteMsg::UpdateConfig {
+        token_code_id: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+        permissioned: None,
+        creation_fee: Some(creation_fee),
+        treasury,
+    };
+    let native_fee = Asset {
+        info: native_token("uluna"),
+        amount: Uint128::from(100u128),
+    };
+
+    // the fee needs a treasury
+    let info = mock_info("addr0000", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_fee(native_fee.clone(), None),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "treasury is required to charge a creation fee")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_fee(native_fee.clone(), Some("treasury0000".to_string())),
+    )
+    .unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(Some(native_fee.clone()), config_res.creation_fee);
+    assert_eq!(Some("treasury0000".to_string()), config_res.treasury);
+
+    let asset_infos = [native_token("uusd"), native_token("ukrw")];
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        amp: None,
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Creation fee of 100uluna is required")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // other funds than the fee are rejected
+    let info = mock_info(
+        "addr0001",
+        &[
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(100u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1u128),
+            },
+        ],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Only the creation fee of 100uluna can be sent")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the native fee is forwarded to the treasury
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+
+    // a native fee is sent along with the liquidity of the same denom
+    let msg = ExecuteMsg::CreatePairWithLiquidity {
+        assets: [
+            Asset {
+                info: native_token("uluna"),
+                amount: Uint128::from(1000u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(1000u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        pair_type: None,
+        amp: None,
+    };
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Native token balance mismatch between the argument and the transferred"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    // funds unrelated to the fee and the deposits are rejected
+    let info = mock_info(
+        "addr0001",
+        &[
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(1100u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1u128),
+            },
+        ],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Only the creation fee of 100uluna can be sent")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+
+    // token fees are pulled from the allowance of the sender
+    let token_fee = Asset {
+        info: AssetInfo::Token {
+            contract_addr: "fee0000".to_string(),
+        },
+        amount: Uint128::from(50u128),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, update_fee(token_fee, None)).unwrap();
+
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "fee0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0001".to_string(),
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))
+    );
+
+    // or sent through the receive hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(50u128),
+        msg: to_binary(&Cw20HookMsg::CreatePair {
+            asset_infos,
+            pair_type: None,
+            amp: None,
+        })
+        .unwrap(),
+    });
+
+    let info = mock_info("asset0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Sent token is not the creation fee")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info("fee0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "fee0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))
+    );
+
+    // a zero fee removes it
+    let info = mock_info("addr0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_fee(
+            Asset {
+                info: native_token("uluna"),
+                amount: Uint128::zero(),
+            },
+            None,
+        ),
+    )
+    .unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(None, config_res.creation_fee);
+
+    // without fee, no funds are kept by the factory
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [native_token("uusd"), native_token("uluna")],
+        pair_type: None,
+        amp: None,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "No creation fee is charged")
+        }
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn deregister_and_register_existing_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_infos = [
+        native_token("uusd"),
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+    ];
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: "pair0000".to_string(),
+        liquidity_token: "liquidity0000".to_string(),
+        paused: false,
+        pair_type: "xyk".to_string(),
+        asset_decimals: [6, 6],
+    };
+    save_pair(
+        &mut deps.storage,
+        &PairInfoRaw {
+            asset_infos: [
+                asset_infos[0].to_raw(&deps.api).unwrap(),
+                asset_infos[1].to_raw(&deps.api).unwrap(),
+            ],
+            contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::DeregisterPair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deregister_pair"),
+            attr("pair", "uusd-asset0000"),
+            attr("pair_contract_addr", "pair0000"),
+        ]
+    );
+
+    // the pair and its index entries are removed
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    )
+    .is_err());
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            contract_addr: "pair0000".to_string(),
+        },
+    )
+    .is_err());
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLiquidityToken {
+            liquidity_token: "liquidity0000".to_string(),
+        },
+    )
+    .is_err());
+    assert!(query_pairs_by_asset(deps.as_ref(), native_token("uusd"), None, None).is_empty());
+
+    // register the fixed pair, which reports wrong decimals
+    let fixed_pair_info = PairInfo {
+        contract_addr: "pair0001".to_string(),
+        liquidity_token: "liquidity0001".to_string(),
+        asset_decimals: [0, 0],
+        ..pair_info
+    };
+    let unknown_type_pair_info = PairInfo {
+        contract_addr: "pair0003".to_string(),
+        liquidity_token: "liquidity0003".to_string(),
+        pair_type: "stable".to_string(),
+        ..fixed_pair_info.clone()
+    };
+    deps.querier.with_terraswap_pairs(&[
+        (&"pair0001".to_string(), &fixed_pair_info),
+        (&"pair0002".to_string(), &fixed_pair_info),
+        (&"pair0003".to_string(), &unknown_type_pair_info),
+    ]);
+    deps.querier
+        .with_token_decimals(&[(&"asset0000".to_string(), &8u8)]);
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+
+    let msg = ExecuteMsg::RegisterExistingPair {
+        pair_addr: "pair0001".to_string(),
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the pair must describe itself
+    let info = mock_info("addr0000", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RegisterExistingPair {
+            pair_addr: "pair0002".to_string(),
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Pair contract address mismatch between the argument and the pair"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    // the pair type must be registered in the factory
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RegisterExistingPair {
+            pair_addr: "pair0003".to_string(),
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Pair type stable is not registered")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_existing_pair"),
+            attr("pair", "uusd-asset0000"),
+            attr("pair_contract_addr", "pair0001"),
+            attr("liquidity_token_addr", "liquidity0001"),
+        ]
+    );
+
+    // the decimals are queried by the factory
+    let fixed_pair_info = PairInfo {
+        asset_decimals: [6, 8],
+        ..fixed_pair_info
+    };
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        fixed_pair_info,
+        from_binary::<PairInfo>(&query_res).unwrap()
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByAddress {
+            contract_addr: "pair0001".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        fixed_pair_info,
+        from_binary::<PairInfo>(&query_res).unwrap()
+    );
+
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Pair already exists"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn pair_types() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    add_native_token_decimals(deps.as_mut(), "uusd", 6);
+    add_native_token_decimals(deps.as_mut(), "uluna", 6);
+
+    let msg = ExecuteMsg::UpdatePairType {
+        pair_type: "stable-0.1".to_string(),
+        code_id: 654u64,
+        curve: PairType::Stable,
+        commission_rate: Some(Decimal::permille(1)),
+    };
+
+    let info = mock_info("addr0001", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pair_type"),
+            attr("pair_type", "stable-0.1"),
+            attr("code_id", "654"),
+        ]
+    );
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(config_res.pair_code_id, 321u64);
+    assert_eq!(
+        config_res.pair_types,
+        vec![
+            PairTypeConfig {
+                pair_type: "xyk".to_string(),
+                code_id: 321u64,
+                curve: PairType::Xyk,
+                commission_rate: Decimal::permille(3),
+            },
+            PairTypeConfig {
+                pair_type: "stable-0.1".to_string(),
+                code_id: 654u64,
+                curve: PairType::Stable,
+                commission_rate: Decimal::permille(1),
+            },
+        ]
+    );
+
+    // the curve of a registered type is fixed, including the default xyk
+    let info = mock_info("addr0000", &[]);
+    for (pair_type, curve) in [("xyk", PairType::Stable), ("stable-0.1", PairType::Xyk)] {
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdatePairType {
+                pair_type: pair_type.to_string(),
+                code_id: 987u64,
+                curve,
+                commission_rate: None,
+            },
+        ) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+                msg,
+                format!("Curve of pair type {} cannot be changed", pair_type)
+            ),
+            _ => panic!("Must return generic error"),
+        }
+    }
+
+    // a new code keeps the commission rate of the type when it is omitted
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdatePairType {
+            pair_type: "stable-0.1".to_string(),
+            code_id: 654u64,
+            curve: PairType::Stable,
+            commission_rate: None,
+        },
+    )
+    .unwrap();
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        config_res.pair_types[1].commission_rate,
+        Decimal::permille(1)
+    );
+
+    // unknown pair types cannot be created
+    let asset_infos = [native_token("uusd"), native_token("uluna")];
+    let info = mock_info("addr0000", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some("concentrated".to_string()),
+            amp: None,
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Pair type concentrated is not registered")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // a stable pair can live next to the xyk pair of the same assets,
+    // the pair is told the registered type name besides its curve
+    save_pair(
+        &mut deps.storage,
+        &PairInfoRaw {
+            asset_infos: [
+                asset_infos[0].to_raw(&deps.api).unwrap(),
+                asset_infos[1].to_raw(&deps.api).unwrap(),
+            ],
+            contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some("stable-0.1".to_string()),
+            amp: Some(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-uluna"),
+            attr("pair_type", "stable-0.1")
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&PairInstantiateMsg {
+                    asset_infos: asset_infos.clone(),
+                    token_code_id: 123u64,
+                    commission_rate: Some(Decimal::permille(1)),
+                    pair_type: Some(PairType::Stable),
+                    pair_type_name: Some("stable-0.1".to_string()),
+                    amp: Some(100),
+                    asset_decimals: [6, 6],
+                })
+                .unwrap(),
+                code_id: 654u64,
+                funds: vec![],
+                label: "".to_string(),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+            }
+            .into()
+        },]
+    );
+
+    save_pair(
+        &mut deps.storage,
+        &PairInfoRaw {
+            asset_infos: [
+                asset_infos[0].to_raw(&deps.api).unwrap(),
+                asset_infos[1].to_raw(&deps.api).unwrap(),
+            ],
+            contract_addr: deps.api.addr_canonicalize("pair0001").unwrap(),
+            liquidity_token: deps.api.addr_canonicalize("liquidity0001").unwrap(),
+            paused: false,
+            pair_type: "stable-0.1".to_string(),
+            asset_decimals: [6, 6],
+        },
+    )
+    .unwrap();
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0000");
+    assert_eq!(pair_res.pair_type, "xyk");
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some("stable-0.1".to_string()),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0001");
+    assert_eq!(pair_res.pair_type, "stable-0.1");
+
+    // the default pair type is kept
+    let info = mock_info("addr0000", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RemovePairType {
+            pair_type: "xyk".to_string(),
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "default pair type cannot be removed")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = ExecuteMsg::RemovePairType {
+        pair_type: "stable-0.1".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "remove_pair_type"),
+            attr("pair_type", "stable-0.1"),
+        ]
+    );
+
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Pair type stable-0.1 is not registered")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the pairs of a removed type stay registered
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type: Some("stable-0.1".to_string()),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0001");
+}
+
+#[test]
+fn native_token_decimals() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        commission_rate: None,
+        protocol_fee_share: None,
+        fee_collector: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::AddNativeTokenDecimals {
+        denom: "uusd".to_string(),
+        decimals: 6,
+    };
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // a deposit of the denom proves it exists
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "A deposit of uusd is required"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // the deposit is sent back
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1u128),
+            }],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "add_native_token_decimals"),
+            attr("denom", "uusd"),
+            attr("decimals", "6"),
+        ]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::NativeTokenDecimals {
+            denom: "uusd".to_string(),
+        },
+    )
+    .unwrap();
+    let decimals_res: NativeTokenDecimalsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(decimals_res.decimals, 6);
+
+    match query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::NativeTokenDecimals {
+            denom: "uluna".to_string(),
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Decimals of uluna are not registered")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the decimals of both assets are resolved at the pair creation
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [
+            native_token("uusd"),
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        pair_type: None,
+        amp: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert!(msg.contains("No token info exists for the contract asset0000"))
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    deps.querier
+        .with_token_decimals(&[(&"asset0000".to_string(), &8u8)]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        TMP_PAIR_INFOS
+            .load(&deps.storage, U64Key::new(1))
+            .unwrap()
+            .asset_decimals,
+        [6, 8]
+    );
+
+    // native tokens without registered decimals have the decimals of the Terra denoms
+    let msg = ExecuteMsg::CreatePair {
+        asset_infos: [native_token("uusd"), native_token("uluna")],
+        pair_type: None,
+        amp: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        TMP_PAIR_INFOS
+            .load(&deps.storage, U64Key::new(2))
+            .unwrap()
+            .asset_decimals,
+        [6, 6]
+    );
+}
+
+#[test]
+fn pairs_paging_across_pair_types() {
+    let mut deps = mock_dependencies(&[]);
+
+    // the stable pair of uluna/uusd must not collide with the xyk pair of uluna/uusdstable
+    for (asset_infos, pair, pair_type) in [
+        (
+            [native_token("uluna"), native_token("uusd")],
+            "pair0000",
+            "xyk",
+        ),
+        (
+            [native_token("uusd"), native_token("uluna")],
+            "pair0001",
+            "stable",
+        ),
+        (
+            [native_token("uluna"), native_token("uusdstable")],
+            "pair0002",
+            "xyk",
+        ),
+    ] {
+        save_pair(
+            &mut deps.storage,
+            &PairInfoRaw {
+                asset_infos: [
+                    asset_infos[0].to_raw(&deps.api).unwrap(),
+                    asset_infos[1].to_raw(&deps.api).unwrap(),
+                ],
+                contract_addr: deps.api.addr_canonicalize(pair).unwrap(),
+                liquidity_token: deps
+                    .api
+                    .addr_canonicalize(&pair.replace("pair", "liquidity"))
+                    .unwrap(),
+                paused: false,
+                pair_type: pair_type.to_string(),
+                asset_decimals: [6, 6],
+            },
+        )
+        .unwrap();
+    }
+
+    // pages of a single pair continue after the pair type of the last pair
+    let mut start_after: Option<[AssetInfo; 2]> = None;
+    let mut start_after_pair_type: Option<String> = None;
+    let mut pairs: Vec<String> = vec![];
+    loop {
+        let res: PairsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Pairs {
+                    start_after: start_after.clone(),
+                    start_after_pair_type: start_after_pair_type.clone(),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let res_by_asset: PairsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PairsByAsset {
+                    asset_info: native_token("uluna"),
+                    start_after: start_after.clone(),
+                    start_after_pair_type: start_after_pair_type.clone(),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res, res_by_asset);
+
+        let pair = match res.pairs.first() {
+            Some(pair) => pair.clone(),
+            None => break,
+        };
+        assert!(pairs.len() < 3, "paging must not repeat pairs");
+
+        pairs.push(pair.contract_addr.clone());
+        start_after = Some(pair.asset_infos);
+        start_after_pair_type = Some(pair.pair_type);
+    }
+
+    assert_eq!(
+        pairs,
+        vec![
+            "pair0000".to_string(),
+            "pair0001".to_string(),
+            "pair0002".to_string(),
+        ]
     );
 }
@@ -1,39 +1,101 @@
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::PAIR_INFO;
+use crate::stableswap::{self, assert_amp, compute_current_amp, MAX_AMP_CHANGE, MIN_RAMP_TIME};
+use crate::state::{
+    AmpConfig, Config, FlashSwapState, Observation, PriceCumulative, AMP_CONFIG, CONFIG,
+    FLASH_SWAP, OBSERVATIONS, OBSERVATIONS_SIZE, OBSERVATION_PERIOD, PAIR_INFO, PRICE_CUMULATIVE,
+    RESERVES,
+};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    from_binary, to_binary, to_vec, Addr, Api, Binary, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, QueryRequest, Reply,
+    ReplyOn, Response, StdError, StdResult, Storage, SubMsg, SystemError, SystemResult, Uint128,
+    WasmMsg, WasmQuery,
 };
+use cw_storage_plus::U64Key;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use integer_sqrt::IntegerSquareRoot;
 use protobuf::Message;
 use std::str::FromStr;
 use terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
+use terraswap::factory::{ConfigResponse as FactoryConfigResponse, QueryMsg as FactoryQueryMsg};
 use terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PairType, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulateProvideSingleSidedResponse, SimulateWithdrawLiquidityResponse, SimulationResponse,
+    TwapResponse,
 };
 use terraswap::querier::query_supply;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use terraswap::version::assert_migrate_version;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:terraswap-pair";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_REPLY_ID: u64 = 1;
+const FLASH_SWAP_REPLY_ID: u64 = 2;
+
+/// Default commission rate == 0.3%
+const DEFAULT_COMMISSION_RATE: &str = "0.003";
+
+/// Unit increments of the offer when the reverse computation falls short of the ask amount,
+/// near the end of the pool one more unit of offer barely raises the return
+const MAX_OFFER_ADJUSTMENTS: u32 = 10;
+
+/// Share minted to the pair itself on the first deposit and never withdrawn,
+/// so a donation to a nearly empty pool cannot inflate the share price
+pub const MINIMUM_LIQUIDITY_AMOUNT: Uint128 = Uint128::new(1_000);
 
-/// Commission rate == 0.3%
-const COMMISSION_RATE: &str = "0.003";
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let commission_rate = match msg.commission_rate {
+        Some(commission_rate) => commission_rate,
+        None => Decimal::from_str(DEFAULT_COMMISSION_RATE)?,
+    };
+    assert_commission_rate(commission_rate)?;
+
+    let pair_type = msg.pair_type.unwrap_or(PairType::Xyk);
+    if pair_type == PairType::Stable {
+        let amp = msg
+            .amp
+            .ok_or_else(|| StdError::generic_err("amp is required for stable pairs"))?;
+        assert_amp(amp)?;
+
+        AMP_CONFIG.save(
+            deps.storage,
+            &AmpConfig {
+                init_amp: amp,
+                init_amp_time: env.block.time.seconds(),
+                next_amp: amp,
+                next_amp_time: env.block.time.seconds(),
+            },
+        )?;
+    }
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            factory: deps.api.addr_canonicalize(info.sender.as_str())?,
+            commission_rate,
+            pair_type: pair_type.clone(),
+        },
+    )?;
+
     let pair_info: &PairInfoRaw = &PairInfoRaw {
         contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
         liquidity_token: CanonicalAddr::from(vec![]),
@@ -41,14 +103,22 @@ pub fn instantiate(
             msg.asset_infos[0].to_raw(deps.api)?,
             msg.asset_infos[1].to_raw(deps.api)?,
         ],
+        paused: false,
+        pair_type: msg.pair_type_name.unwrap_or_else(|| pair_type.to_string()),
+        asset_decimals: msg.asset_decimals,
     };
 
     PAIR_INFO.save(deps.storage, pair_info)?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    PRICE_CUMULATIVE.save(
+        deps.storage,
+        &new_price_cumulative(env.block.time.seconds()),
+    )?;
 
     Ok(Response::new().add_submessage(SubMsg {
-        // Create LP token
+        // Create LP token, the factory is its admin so it can be migrated
         msg: WasmMsg::Instantiate {
-            admin: None,
+            admin: Some(info.sender.to_string()),
             code_id: msg.token_code_id,
             msg: to_binary(&TokenInstantiateMsg {
                 name: "terraswap liquidity token".to_string(),
@@ -77,19 +147,44 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // the pair is locked until the flash swap is paid back
+    if FLASH_SWAP.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
             receiver,
-        } => provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver),
+            deadline,
+        } => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+            provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver)
+        }
+        ExecuteMsg::ProvideSingleSided {
+            asset,
+            slippage_tolerance,
+            receiver,
+            deadline,
+        } => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+            if !asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            asset.assert_sent_native_token_balance(&info)?;
+            provide_single_sided(deps, env, info.sender, asset, slippage_tolerance, receiver)
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Unauthorized {});
             }
@@ -111,6 +206,42 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::UpdateConfig { commission_rate } => update_config(deps, info, commission_rate),
+        ExecuteMsg::RampAmp {
+            future_amp,
+            future_time,
+        } => ramp_amp(deps, env, info, future_amp, future_time),
+        ExecuteMsg::StopRampAmp {} => stop_ramp_amp(deps, env, info),
+        ExecuteMsg::SetStatus { paused } => set_status(deps, info, paused),
+        ExecuteMsg::Skim {} => skim(deps, env, info),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                ask_asset,
+                max_offer_amount,
+                to_addr,
+            )
+        }
+        ExecuteMsg::FlashSwap {
+            ask_asset,
+            callback_msg,
+        } => flash_swap(deps, env, info, ask_asset, callback_msg),
     }
 }
 
@@ -127,12 +258,14 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         }) => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+
             // only asset contract can execute this message
             let mut authorized: bool = false;
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-            let pools: [Asset; 2] =
-                config.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+            let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &config)?;
             for pool in pools.iter() {
                 if let AssetInfo::Token { contract_addr, .. } = &pool.info {
                     if contract_addr == &info.sender {
@@ -167,22 +300,440 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => {
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            // the sent amount is the max offer, swap_exact_out checks the sender is the offer token
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                ask_asset,
+                cw20_msg.amount,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::ProvideSingleSided {
+            slippage_tolerance,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(env.block.time.seconds(), deadline)?;
+
+            // only asset contract can execute this message
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &config)?;
+            let authorized = pools.iter().any(|pool| match &pool.info {
+                AssetInfo::Token { contract_addr, .. } => contract_addr == &info.sender,
+                AssetInfo::NativeToken { .. } => false,
+            });
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            provide_single_sided(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contract_addr.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                slippage_tolerance,
+                receiver,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets,
+            deadline,
+        }) => {
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
             if deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token {
                 return Err(ContractError::Unauthorized {});
             }
 
+            assert_deadline(env.block.time.seconds(), deadline)?;
+
             let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
-            withdraw_liquidity(deps, env, info, sender_addr, cw20_msg.amount)
+            withdraw_liquidity(deps, env, sender_addr, cw20_msg.amount, min_assets)
         }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
 
+// Only factory can execute it
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    commission_rate: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        assert_commission_rate(commission_rate)?;
+        config.commission_rate = commission_rate;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_config"),
+        ("commission_rate", &config.commission_rate.to_string()),
+    ]))
+}
+
+// Only factory can execute it
+pub fn set_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAIR_INFO.update(deps.storage, |mut pair_info| -> StdResult<_> {
+        pair_info.paused = paused;
+        Ok(pair_info)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_status"),
+        ("paused", &paused.to_string()),
+    ]))
+}
+
+/// Skim sends the balances exceeding the reserves to the sender
+pub fn skim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let balances: [Asset; 2] =
+        pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut skimmed_assets: Vec<Asset> = vec![];
+    for (pool, balance) in pools.iter().zip(balances.iter()) {
+        let skimmed_asset = Asset {
+            info: pool.info.clone(),
+            amount: balance.amount.saturating_sub(pool.amount),
+        };
+
+        if !skimmed_asset.amount.is_zero() {
+            messages.push(
+                skimmed_asset
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+
+        skimmed_assets.push(skimmed_asset);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "skim"),
+        ("sender", info.sender.as_str()),
+        (
+            "skimmed_assets",
+            &format!("{}, {}", skimmed_assets[0], skimmed_assets[1]),
+        ),
+    ]))
+}
+
+/// Sync sets the reserves to the balances of the pair
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let balances: [Asset; 2] =
+        pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+
+    // accumulate prices with the reserves before the sync
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+    RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync"),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// Sends `ask_asset` to the sender before it pays for it,
+/// the payment is checked when the callback replies
+pub fn flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ask_asset: Asset,
+    callback_msg: Binary,
+) -> Result<Response, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    if pair_info.paused {
+        return Err(ContractError::Paused {});
+    }
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let ask_index = find_pool_index(&pools, &ask_asset.info)?;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if ask_asset.amount >= pools[ask_index].amount {
+        return Err(StdError::generic_err("ask amount exceeds the pool").into());
+    }
+
+    // accumulate prices with the pools before the flash swap
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
+    let mut borrowed = [Uint128::zero(), Uint128::zero()];
+    borrowed[ask_index] = ask_asset.amount;
+    FLASH_SWAP.save(
+        deps.storage,
+        &FlashSwapState {
+            borrower: deps.api.addr_canonicalize(info.sender.as_str())?,
+            reserves: [pools[0].amount, pools[1].amount],
+            borrowed,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_message(
+            ask_asset
+                .clone()
+                .into_msg(&deps.querier, info.sender.clone())?,
+        )
+        .add_submessage(SubMsg {
+            id: FLASH_SWAP_REPLY_ID,
+            msg: WasmMsg::Execute {
+                contract_addr: info.sender.to_string(),
+                msg: callback_msg,
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+        })
+        .add_attributes(vec![
+            ("action", "flash_swap"),
+            ("borrower", info.sender.as_str()),
+            ("ask_asset", &ask_asset.to_string()),
+        ]))
+}
+
+/// The balances after the callback, less the commission on the amounts paid in,
+/// must keep the invariant of the reserves before the flash swap.
+/// The protocol fee is taken from the commission as for the other swaps
+fn reply_flash_swap(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let flash_swap: FlashSwapState = FLASH_SWAP.load(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let balances: [Asset; 2] =
+        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+
+    let mut commission_amounts = [Uint128::zero(), Uint128::zero()];
+    let mut adjusted_balances = [Uint128::zero(), Uint128::zero()];
+    for i in 0..2 {
+        let amount_in = balances[i]
+            .amount
+            .saturating_sub(flash_swap.reserves[i].checked_sub(flash_swap.borrowed[i])?);
+        commission_amounts[i] = amount_in * config.commission_rate;
+        adjusted_balances[i] = balances[i].amount.checked_sub(commission_amounts[i])?;
+    }
+
+    let invariant_kept = match config.pair_type {
+        PairType::Xyk => {
+            Uint256::from(adjusted_balances[0]) * Uint256::from(adjusted_balances[1])
+                >= Uint256::from(flash_swap.reserves[0]) * Uint256::from(flash_swap.reserves[1])
+        }
+        PairType::Stable => {
+            let amp_config: AmpConfig = load_amp_config(deps.as_ref(), &config)?;
+            let amp = compute_current_amp(&amp_config, env.block.time.seconds());
+            !adjusted_balances[0].is_zero()
+                && !adjusted_balances[1].is_zero()
+                && stableswap::compute_invariant(amp, adjusted_balances, pair_info.asset_decimals)
+                    >= stableswap::compute_invariant(
+                        amp,
+                        flash_swap.reserves,
+                        pair_info.asset_decimals,
+                    )
+        }
+    };
+    if !invariant_kept {
+        return Err(ContractError::FlashSwapInvariantViolation {});
+    }
+
+    let (protocol_fee_share, fee_collector) = query_protocol_fee_share(deps.as_ref(), &config)?;
+    let protocol_fee_assets: Vec<Asset> = balances
+        .iter()
+        .zip(commission_amounts.iter())
+        .map(|(balance, commission_amount)| Asset {
+            info: balance.info.clone(),
+            amount: *commission_amount * protocol_fee_share,
+        })
+        .collect();
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(fee_collector) = fee_collector {
+        for protocol_fee_asset in protocol_fee_assets.iter() {
+            if !protocol_fee_asset.amount.is_zero() {
+                messages.push(
+                    protocol_fee_asset
+                        .clone()
+                        .into_msg(&deps.querier, fee_collector.clone())?,
+                );
+            }
+        }
+    }
+
+    // the rest of the commission stays in the pool
+    let reserves = [
+        balances[0]
+            .amount
+            .checked_sub(protocol_fee_assets[0].amount)?,
+        balances[1]
+            .amount
+            .checked_sub(protocol_fee_assets[1].amount)?,
+    ];
+    FLASH_SWAP.remove(deps.storage);
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "flash_swap_repaid"),
+        (
+            "borrower",
+            deps.api.addr_humanize(&flash_swap.borrower)?.as_str(),
+        ),
+        (
+            "reserves",
+            &format!(
+                "{}{}, {}{}",
+                reserves[0], balances[0].info, reserves[1], balances[1].info
+            ),
+        ),
+        (
+            "protocol_fee_assets",
+            &format!("{}, {}", protocol_fee_assets[0], protocol_fee_assets[1]),
+        ),
+    ]))
+}
+
+// Only factory can execute it
+pub fn ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    future_amp: u64,
+    future_time: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amp_config: AmpConfig = load_amp_config(deps.as_ref(), &config)?;
+    let block_time = env.block.time.seconds();
+    let current_amp = compute_current_amp(&amp_config, block_time);
+
+    assert_amp(future_amp)?;
+    if future_time < block_time + MIN_RAMP_TIME {
+        return Err(StdError::generic_err(format!(
+            "amp ramp must last at least {} seconds",
+            MIN_RAMP_TIME
+        ))
+        .into());
+    }
+
+    if future_amp > current_amp * MAX_AMP_CHANGE || future_amp * MAX_AMP_CHANGE < current_amp {
+        return Err(StdError::generic_err(format!(
+            "amp can change at most {} times in a ramp",
+            MAX_AMP_CHANGE
+        ))
+        .into());
+    }
+
+    AMP_CONFIG.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: block_time,
+            next_amp: future_amp,
+            next_amp_time: future_time,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "ramp_amp"),
+        ("init_amp", &current_amp.to_string()),
+        ("future_amp", &future_amp.to_string()),
+        ("future_time", &future_time.to_string()),
+    ]))
+}
+
+// Only factory can execute it
+pub fn stop_ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amp_config: AmpConfig = load_amp_config(deps.as_ref(), &config)?;
+    let block_time = env.block.time.seconds();
+    let current_amp = compute_current_amp(&amp_config, block_time);
+
+    AMP_CONFIG.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: block_time,
+            next_amp: current_amp,
+            next_amp_time: block_time,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "stop_ramp_amp"),
+        ("amp", &current_amp.to_string()),
+    ]))
+}
+
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => Ok(reply_instantiate(deps, msg)?),
+        FLASH_SWAP_REPLY_ID => reply_flash_swap(deps, env),
+        _ => Err(StdError::generic_err("invalid reply id").into()),
+    }
+}
+
+fn reply_instantiate(deps: DepsMut, msg: Reply) -> StdResult<Response> {
     let data = msg.result.unwrap().data.unwrap();
     let res: MsgInstantiateContractResponse =
         Message::parse_from_bytes(data.as_slice()).map_err(|_| {
@@ -213,49 +764,56 @@ pub fn provide_liquidity(
     }
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+    if pair_info.paused {
+        return Err(ContractError::Paused {});
+    }
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
     let deposits: [Uint128; 2] = [
         assets
             .iter()
             .find(|a| a.info.equal(&pools[0].info))
             .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
+            .ok_or(ContractError::AssetMismatch {})?,
         assets
             .iter()
             .find(|a| a.info.equal(&pools[1].info))
             .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
+            .ok_or(ContractError::AssetMismatch {})?,
     ];
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
-        // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
-        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: contract_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: env.contract.address.to_string(),
-                    amount: deposits[i],
-                })?,
-                funds: vec![],
-            }));
-        } else {
-            // If the asset is native token, balance is already increased
-            // To calculated properly we should subtract user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
-        }
-    }
-
     // assert slippage tolerance
     assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
 
+    // accumulate prices with the pools before the deposit
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
     let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
-    let total_share = query_supply(&deps.querier, liquidity_token)?;
+    let total_share = query_supply(&deps.querier, liquidity_token.clone())?;
+
+    // only the deposits matching the pool ratio are taken, the rest is refunded
+    let (deposits, refunds): ([Uint128; 2], [Uint128; 2]) = if total_share.is_zero() {
+        (deposits, [Uint128::zero(), Uint128::zero()])
+    } else {
+        let optimal_deposits = compute_optimal_deposits(&deposits, &pools);
+        (
+            optimal_deposits,
+            [
+                deposits[0].checked_sub(optimal_deposits[0])?,
+                deposits[1].checked_sub(optimal_deposits[1])?,
+            ],
+        )
+    };
+
+    let mut locked_share = Uint128::zero();
     let share = if total_share == Uint128::zero() {
         // Initial share = collateral amount
-        Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt())
+        let share = Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt());
+        if share <= MINIMUM_LIQUIDITY_AMOUNT {
+            return Err(ContractError::MinimumLiquidityAmountError {});
+        }
+
+        locked_share = MINIMUM_LIQUIDITY_AMOUNT;
+        share.checked_sub(locked_share)?
     } else {
         // min(1, 2)
         // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
@@ -273,13 +831,47 @@ pub fn provide_liquidity(
         return Err(ContractError::InvalidZeroAmount {});
     }
 
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
+        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: deposits[i],
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    // the taken deposits are added to the reserves
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+    )?;
+
+    // lock the minimum liquidity in the pair on the first deposit
+    if !locked_share.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: locked_share,
+            })?,
+            funds: vec![],
+        }));
+    }
+
     // mint LP token to sender
     let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&pair_info.liquidity_token)?
-            .to_string(),
+        contract_addr: liquidity_token.to_string(),
         msg: to_binary(&Cw20ExecuteMsg::Mint {
             recipient: receiver.to_string(),
             amount: share,
@@ -287,38 +879,160 @@ pub fn provide_liquidity(
         funds: vec![],
     }));
 
+    // the excess of tokens is never transferred, so only native funds are sent back
+    let refund_assets: [Asset; 2] = [
+        Asset {
+            info: pools[0].info.clone(),
+            amount: refunds[0],
+        },
+        Asset {
+            info: pools[1].info.clone(),
+            amount: refunds[1],
+        },
+    ];
+    for refund_asset in refund_assets.iter() {
+        if refund_asset.is_native_token() && !refund_asset.amount.is_zero() {
+            messages.push(
+                refund_asset
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+    }
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         ("action", "provide_liquidity"),
         ("sender", info.sender.as_str()),
         ("receiver", receiver.as_str()),
         ("assets", &format!("{}, {}", assets[0], assets[1])),
         ("share", &share.to_string()),
+        (
+            "refund_assets",
+            &format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ]))
+}
+
+/// Swaps the part of `asset` that balances the rest of it
+/// against the pools and provides both as liquidity
+pub fn provide_single_sided(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    asset: Asset,
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    if pair_info.paused {
+        return Err(ContractError::Paused {});
+    }
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let offer_index = find_pool_index(&pools, &asset.info)?;
+
+    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+    let total_share = query_supply(&deps.querier, liquidity_token.clone())?;
+
+    let provision = compute_single_sided_provision(
+        deps.as_ref(),
+        &env,
+        &config,
+        &pools,
+        offer_index,
+        asset.amount,
+        total_share,
+    )?;
+
+    // check max spread of the internal swap
+    assert_max_spread(
+        None,
+        slippage_tolerance,
+        provision.swap_amount,
+        provision.return_amount + provision.commission_amount,
+        provision.spread_amount,
+    )?;
+
+    // accumulate prices with the pools before the deposit
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
+    // the whole deposit is added to the reserves, minus the protocol fee of the swap
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(asset.amount)?;
+    reserves[1 - offer_index] =
+        reserves[1 - offer_index].checked_sub(provision.protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let receiver = receiver.unwrap_or_else(|| sender.to_string());
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: provision.share,
+        })?,
+        funds: vec![],
+    })];
+
+    if let Some(fee_collector) = provision.fee_collector {
+        if !provision.protocol_fee_amount.is_zero() {
+            messages.push(
+                Asset {
+                    info: pools[1 - offer_index].info.clone(),
+                    amount: provision.protocol_fee_amount,
+                }
+                .into_msg(&deps.querier, fee_collector)?,
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_single_sided"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("asset", &asset.to_string()),
+        ("swap_amount", &provision.swap_amount.to_string()),
+        ("return_amount", &provision.return_amount.to_string()),
+        ("spread_amount", &provision.spread_amount.to_string()),
+        (
+            "commission_amount",
+            &provision.commission_amount.to_string(),
+        ),
+        (
+            "protocol_fee_amount",
+            &provision.protocol_fee_amount.to_string(),
+        ),
+        ("share", &provision.share.to_string()),
     ]))
 }
 
 pub fn withdraw_liquidity(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    min_assets: Option<[Asset; 2]>,
 ) -> Result<Response, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
     let total_share: Uint128 = query_supply(&deps.querier, liquidity_addr)?;
 
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
-    let refund_assets: Vec<Asset> = pools
-        .iter()
-        .map(|a| Asset {
-            info: a.info.clone(),
-            amount: a.amount * share_ratio,
-        })
-        .collect();
+    // accumulate prices with the pools before the withdrawal
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
+    let refund_assets: [Asset; 2] = compute_refund_assets(&pools, amount, total_share)?;
+    assert_min_assets(&refund_assets, min_assets)?;
 
     // update pool info
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(refund_assets[0].amount)?,
+            pools[1].amount.checked_sub(refund_assets[1].amount)?,
+        ],
+    )?;
+
     Ok(Response::new()
         .add_messages(vec![
             refund_assets[0]
@@ -362,34 +1076,39 @@ pub fn swap(
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
+    let config: Config = CONFIG.load(deps.storage)?;
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    if pair_info.paused {
+        return Err(ContractError::Paused {});
+    }
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
-
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
 
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
+    let offer_index: usize;
     if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = Asset {
-            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
-            info: pools[0].info.clone(),
-        };
-        ask_pool = pools[1].clone();
+        offer_index = 0;
     } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = Asset {
-            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
-            info: pools[1].info.clone(),
-        };
-        ask_pool = pools[0].clone();
+        offer_index = 1;
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    // accumulate prices with the pools before the swap
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
     let offer_amount = offer_asset.amount;
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_amount);
+    let (return_amount, spread_amount, commission_amount) = compute_swap_by_type(
+        deps.as_ref(),
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        swap_decimals(&pair_info, offer_index),
+    )?;
 
     // check max spread limit if exist
     assert_max_spread(
@@ -409,11 +1128,34 @@ pub fn swap(
     let tax_amount = return_asset.compute_tax(&deps.querier)?;
     let receiver = to.unwrap_or_else(|| sender.clone());
 
+    let (protocol_fee_amount, fee_collector) =
+        compute_protocol_fee(deps.as_ref(), &config, commission_amount)?;
+
+    // the rest of the commission stays in the pool
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(offer_amount)?;
+    reserves[1 - offer_index] = reserves[1 - offer_index]
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
     let mut messages: Vec<CosmosMsg> = vec![];
     if !return_amount.is_zero() {
         messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
     }
 
+    if let Some(fee_collector) = fee_collector {
+        if !protocol_fee_amount.is_zero() {
+            messages.push(
+                Asset {
+                    info: ask_pool.info.clone(),
+                    amount: protocol_fee_amount,
+                }
+                .into_msg(&deps.querier, fee_collector)?,
+            );
+        }
+    }
+
     // 1. send collateral token from the contract to a user
     // 2. send inactive commission to collector
     Ok(Response::new().add_messages(messages).add_attributes(vec![
@@ -427,20 +1169,176 @@ pub fn swap(
         ("tax_amount", &tax_amount.to_string()),
         ("spread_amount", &spread_amount.to_string()),
         ("commission_amount", &commission_amount.to_string()),
+        ("protocol_fee_amount", &protocol_fee_amount.to_string()),
+    ]))
+}
+
+/// Sends exactly `ask_asset` for the least offer covering it
+/// and refunds the rest of `max_offer_amount` to the sender
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    ask_asset: Asset,
+    max_offer_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    if pair_info.paused {
+        return Err(ContractError::Paused {});
+    }
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+
+    let ask_index = find_pool_index(&pools, &ask_asset.info)?;
+    let offer_pool: Asset = pools[1 - ask_index].clone();
+    let ask_pool: Asset = pools[ask_index].clone();
+
+    // tokens can only be offered through the hook of the token contract
+    let offer_asset = Asset {
+        info: offer_pool.info.clone(),
+        amount: max_offer_amount,
+    };
+    match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => {
+            if contract_addr != info.sender.as_str() {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+        AssetInfo::NativeToken { .. } => offer_asset.assert_sent_native_token_balance(&info)?,
+    }
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // accumulate prices with the pools before the swap
+    accumulate_prices(deps.storage, env.block.time.seconds(), &pools)?;
+
+    let decimals = swap_decimals(&pair_info, 1 - ask_index);
+    let (mut offer_amount, _, _) = compute_offer_amount_by_type(
+        deps.as_ref(),
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        decimals,
+    )?;
+
+    // the reverse computation can round in favor of the trader,
+    // so the offer is raised until the swap covers the ask amount
+    let mut swap_result = compute_swap_by_type(
+        deps.as_ref(),
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        decimals,
+    )?;
+    let mut offer_adjustments: u32 = 0;
+    while swap_result.0 < ask_asset.amount {
+        if offer_adjustments == MAX_OFFER_ADJUSTMENTS {
+            return Err(ContractError::AskAmountUnreachable {});
+        }
+        offer_adjustments += 1;
+
+        offer_amount = offer_amount.checked_add(Uint128::from(1u128))?;
+        swap_result = compute_swap_by_type(
+            deps.as_ref(),
+            &env,
+            &config,
+            offer_pool.amount,
+            ask_pool.amount,
+            offer_amount,
+            decimals,
+        )?;
+    }
+    let (_, spread_amount, commission_amount) = swap_result;
+
+    if offer_amount > max_offer_amount {
+        return Err(ContractError::MaxOfferAmountAssertion {});
+    }
+
+    let tax_amount = ask_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let (protocol_fee_amount, fee_collector) =
+        compute_protocol_fee(deps.as_ref(), &config, commission_amount)?;
+
+    // a return exceeding the ask amount stays in the pool
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[1 - ask_index] = reserves[1 - ask_index].checked_add(offer_amount)?;
+    reserves[ask_index] = reserves[ask_index]
+        .checked_sub(ask_asset.amount)?
+        .checked_sub(protocol_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![ask_asset
+        .clone()
+        .into_msg(&deps.querier, receiver.clone())?];
+
+    let refund_amount = max_offer_amount.checked_sub(offer_amount)?;
+    if !refund_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: offer_pool.info.clone(),
+                amount: refund_amount,
+            }
+            .into_msg(&deps.querier, sender.clone())?,
+        );
+    }
+
+    if let Some(fee_collector) = fee_collector {
+        if !protocol_fee_amount.is_zero() {
+            messages.push(
+                Asset {
+                    info: ask_pool.info.clone(),
+                    amount: protocol_fee_amount,
+                }
+                .into_msg(&deps.querier, fee_collector)?,
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap_exact_out"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_pool.info.to_string()),
+        ("ask_asset", &ask_asset.info.to_string()),
+        ("offer_amount", &offer_amount.to_string()),
+        ("return_amount", &ask_asset.amount.to_string()),
+        ("refund_amount", &refund_amount.to_string()),
+        ("tax_amount", &tax_amount.to_string()),
+        ("spread_amount", &spread_amount.to_string()),
+        ("commission_amount", &commission_amount.to_string()),
+        ("protocol_fee_amount", &protocol_fee_amount.to_string()),
     ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
         QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
         QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps, env)?)?),
         QueryMsg::Simulation { offer_asset } => {
-            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
+            Ok(to_binary(&query_simulation(deps, env, offer_asset)?)?)
         }
         QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
+            Ok(to_binary(&query_reverse_simulation(deps, env, ask_asset)?)?)
         }
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::Twap { seconds_ago } => Ok(to_binary(&query_twap(deps, env, seconds_ago)?)?),
+        QueryMsg::SimulateWithdrawLiquidity { lp_amount } => Ok(to_binary(
+            &query_simulate_withdraw_liquidity(deps, lp_amount)?,
+        )?),
+        QueryMsg::SimulateProvideSingleSided { asset } => Ok(to_binary(
+            &query_simulate_provide_single_sided(deps, env, asset)?,
+        )?),
     }
 }
 
@@ -453,8 +1351,7 @@ pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
 
 pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let assets: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let assets: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
     let total_share: Uint128 = query_supply(
         &deps.querier,
         deps.api.addr_humanize(&pair_info.liquidity_token)?,
@@ -468,60 +1365,149 @@ pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
     Ok(resp)
 }
 
+pub fn query_simulate_withdraw_liquidity(
+    deps: Deps,
+    lp_amount: Uint128,
+) -> Result<SimulateWithdrawLiquidityResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let total_share: Uint128 = query_supply(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?;
+
+    if lp_amount > total_share {
+        return Err(ContractError::Std(StdError::generic_err(
+            "lp_amount exceeds the total share",
+        )));
+    }
+
+    Ok(SimulateWithdrawLiquidityResponse {
+        refund_assets: compute_refund_assets(&pools, lp_amount, total_share)?,
+    })
+}
+
+pub fn query_simulate_provide_single_sided(
+    deps: Deps,
+    env: Env,
+    asset: Asset,
+) -> Result<SimulateProvideSingleSidedResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+    let offer_index = find_pool_index(&pools, &asset.info)?;
+    let total_share: Uint128 = query_supply(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?;
+
+    let provision = compute_single_sided_provision(
+        deps,
+        &env,
+        &config,
+        &pools,
+        offer_index,
+        asset.amount,
+        total_share,
+    )?;
+
+    Ok(SimulateProvideSingleSidedResponse {
+        swap_amount: provision.swap_amount,
+        return_amount: provision.return_amount,
+        spread_amount: provision.spread_amount,
+        commission_amount: provision.commission_amount,
+        share: provision.share,
+    })
+}
+
+pub fn query_config(deps: Deps, env: Env) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let amp = match AMP_CONFIG.may_load(deps.storage)? {
+        Some(amp_config) => Some(compute_current_amp(&amp_config, env.block.time.seconds())),
+        None => None,
+    };
+
+    let resp = ConfigResponse {
+        factory: deps.api.addr_humanize(&config.factory)?.to_string(),
+        commission_rate: config.commission_rate,
+        pair_type: config.pair_type,
+        amp,
+    };
+
+    Ok(resp)
+}
+
 pub fn query_simulation(
     deps: Deps,
+    env: Env,
     offer_asset: Asset,
 ) -> Result<SimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let offer_index: usize;
     if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
+        offer_index = 0;
     } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
+        offer_index = 1;
     } else {
         return Err(ContractError::AssetMismatch {});
     }
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap_by_type(
+        deps,
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        swap_decimals(&pair_info, offer_index),
+    )?;
 
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_asset.amount);
+    let (protocol_fee_amount, _) = compute_protocol_fee(deps, &config, commission_amount)?;
 
     Ok(SimulationResponse {
         return_amount,
         spread_amount,
         commission_amount,
+        protocol_fee_amount,
     })
 }
 
 pub fn query_reverse_simulation(
     deps: Deps,
+    env: Env,
     ask_asset: Asset,
 ) -> Result<ReverseSimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let ask_index: usize;
     if ask_asset.info.equal(&pools[0].info) {
-        ask_pool = pools[0].clone();
-        offer_pool = pools[1].clone();
+        ask_index = 0;
     } else if ask_asset.info.equal(&pools[1].info) {
-        ask_pool = pools[1].clone();
-        offer_pool = pools[0].clone();
+        ask_index = 1;
     } else {
         return Err(ContractError::AssetMismatch {});
     }
-
-    let (offer_amount, spread_amount, commission_amount) =
-        compute_offer_amount(offer_pool.amount, ask_pool.amount, ask_asset.amount)?;
+    let offer_pool: Asset = pools[1 - ask_index].clone();
+    let ask_pool: Asset = pools[ask_index].clone();
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount_by_type(
+        deps,
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        swap_decimals(&pair_info, 1 - ask_index),
+    )?;
 
     Ok(ReverseSimulationResponse {
         offer_amount,
@@ -530,6 +1516,63 @@ pub fn query_reverse_simulation(
     })
 }
 
+pub fn query_cumulative_prices(
+    deps: Deps,
+    env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let assets: [Asset; 2] = load_pools(deps.storage, deps.api, &pair_info)?;
+
+    let block_time = env.block.time.seconds();
+    let price_cumulative = PRICE_CUMULATIVE
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| new_price_cumulative(block_time));
+    let (price0_cumulative_last, price1_cumulative_last) =
+        compute_cumulative_prices(deps.storage, &price_cumulative, &assets, block_time)?;
+
+    Ok(CumulativePricesResponse {
+        assets,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        block_time_last: block_time,
+    })
+}
+
+pub fn query_twap(deps: Deps, env: Env, seconds_ago: u64) -> Result<TwapResponse, ContractError> {
+    if seconds_ago == 0 {
+        return Err(StdError::generic_err("seconds_ago must be bigger than zero").into());
+    }
+
+    // the latest observation which is not newer than the requested time
+    let target_time = env.block.time.seconds().saturating_sub(seconds_ago);
+    let observation = OBSERVATIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, observation)| observation))
+        .collect::<StdResult<Vec<Observation>>>()?
+        .into_iter()
+        .filter(|observation| observation.timestamp <= target_time)
+        .max_by_key(|observation| observation.timestamp)
+        .ok_or_else(|| StdError::generic_err("no observation is old enough"))?;
+
+    let current = query_cumulative_prices(deps, env)?;
+    let time_elapsed = Decimal256::from_uint256(Uint256::from(
+        current.block_time_last - observation.timestamp,
+    ));
+
+    Ok(TwapResponse {
+        asset_infos: [
+            current.assets[0].info.clone(),
+            current.assets[1].info.clone(),
+        ],
+        price0_average: (current.price0_cumulative_last - observation.price0_cumulative)
+            / time_elapsed,
+        price1_average: (current.price1_cumulative_last - observation.price1_cumulative)
+            / time_elapsed,
+        from_time: observation.timestamp,
+        to_time: current.block_time_last,
+    })
+}
+
 pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
     match coins.iter().find(|x| x.denom == denom) {
         Some(coin) => coin.amount,
@@ -537,16 +1580,117 @@ pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
     }
 }
 
+/// Returns the pools of the pair from the stored reserves
+fn load_pools(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    pair_info: &PairInfoRaw,
+) -> StdResult<[Asset; 2]> {
+    let reserves: [Uint128; 2] = RESERVES.load(storage)?;
+    Ok([
+        Asset {
+            info: pair_info.asset_infos[0].to_normal(api)?,
+            amount: reserves[0],
+        },
+        Asset {
+            info: pair_info.asset_infos[1].to_normal(api)?,
+            amount: reserves[1],
+        },
+    ])
+}
+
+fn find_pool_index(pools: &[Asset; 2], asset_info: &AssetInfo) -> Result<usize, ContractError> {
+    pools
+        .iter()
+        .position(|pool| pool.info.equal(asset_info))
+        .ok_or(ContractError::AssetMismatch {})
+}
+
+fn load_amp_config(deps: Deps, config: &Config) -> StdResult<AmpConfig> {
+    if config.pair_type != PairType::Stable {
+        return Err(StdError::generic_err("not a stable pair"));
+    }
+
+    AMP_CONFIG.load(deps.storage)
+}
+
+/// Decimals of the offer and the ask asset
+fn swap_decimals(pair_info: &PairInfoRaw, offer_index: usize) -> [u8; 2] {
+    [
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[1 - offer_index],
+    ]
+}
+
+/// Dispatches to the invariant of the pair, `decimals` are the decimals of the offer and the ask asset
+fn compute_swap_by_type(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    decimals: [u8; 2],
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    match config.pair_type {
+        PairType::Xyk => Ok(compute_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            config.commission_rate,
+        )),
+        PairType::Stable => {
+            let amp_config: AmpConfig = load_amp_config(deps, config)?;
+            stableswap::compute_swap(
+                offer_pool,
+                ask_pool,
+                offer_amount,
+                config.commission_rate,
+                compute_current_amp(&amp_config, env.block.time.seconds()),
+                decimals,
+            )
+        }
+    }
+}
+
+/// Dispatches the reverse computation to the invariant of the pair
+fn compute_offer_amount_by_type(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    decimals: [u8; 2],
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    match config.pair_type {
+        PairType::Xyk => {
+            compute_offer_amount(offer_pool, ask_pool, ask_amount, config.commission_rate)
+        }
+        PairType::Stable => {
+            let amp_config: AmpConfig = load_amp_config(deps, config)?;
+            stableswap::compute_offer_amount(
+                offer_pool,
+                ask_pool,
+                ask_amount,
+                config.commission_rate,
+                compute_current_amp(&amp_config, env.block.time.seconds()),
+                decimals,
+            )
+        }
+    }
+}
+
 fn compute_swap(
     offer_pool: Uint128,
     ask_pool: Uint128,
     offer_amount: Uint128,
+    commission_rate: Decimal,
 ) -> (Uint128, Uint128, Uint128) {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let offer_amount: Uint256 = offer_amount.into();
-
-    let commission_rate = Decimal256::from_str(COMMISSION_RATE).unwrap();
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // offer => ask
     // ask_amount = (ask_pool - cp / (offer_pool + offer_amount)) * (1 - commission_rate)
@@ -575,7 +1719,13 @@ fn test_compute_swap_with_huge_pool_variance() {
     let ask_pool = Uint128::from(317u128);
 
     assert_eq!(
-        compute_swap(offer_pool, ask_pool, Uint128::from(1u128)).0,
+        compute_swap(
+            offer_pool,
+            ask_pool,
+            Uint128::from(1u128),
+            Decimal::permille(3)
+        )
+        .0,
         Uint128::zero()
     );
 }
@@ -584,12 +1734,12 @@ fn compute_offer_amount(
     offer_pool: Uint128,
     ask_pool: Uint128,
     ask_amount: Uint128,
+    commission_rate: Decimal,
 ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let ask_amount: Uint256 = ask_amount.into();
-
-    let commission_rate = Decimal256::from_str(COMMISSION_RATE).unwrap();
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // ask => offer
     // offer_amount = cp / (ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
@@ -598,11 +1748,14 @@ fn compute_offer_amount(
     let one_minus_commission = Decimal256::one() - commission_rate;
     let inv_one_minus_commission = Decimal256::one() / one_minus_commission;
 
-    let offer_amount: Uint256 = Uint256::one()
-        .multiply_ratio(cp, ask_pool - ask_amount * inv_one_minus_commission)
-        - offer_pool;
-
     let before_commission_deduction: Uint256 = ask_amount * inv_one_minus_commission;
+    if before_commission_deduction >= ask_pool {
+        return Err(StdError::generic_err("ask amount exceeds the pool").into());
+    }
+
+    let offer_amount: Uint256 =
+        Uint256::one().multiply_ratio(cp, ask_pool - before_commission_deduction) - offer_pool;
+
     let before_spread_deduction: Uint256 =
         offer_amount * Decimal256::from_ratio(ask_pool, offer_pool);
 
@@ -626,6 +1779,292 @@ fn compute_offer_amount(
     ))
 }
 
+/// The factory decides which share of the commission goes to the fee collector,
+/// the rest of the commission is absorbed to pool
+fn compute_protocol_fee(
+    deps: Deps,
+    config: &Config,
+    commission_amount: Uint128,
+) -> StdResult<(Uint128, Option<Addr>)> {
+    let (protocol_fee_share, fee_collector) = query_protocol_fee_share(deps, config)?;
+    Ok((commission_amount * protocol_fee_share, fee_collector))
+}
+
+/// Share of the commission sent to the fee collector, zero without a collector
+/// or when the pair has no factory contract, e.g. for a pair instantiated directly by an account.
+/// Any other failure of the factory query is returned
+fn query_protocol_fee_share(deps: Deps, config: &Config) -> StdResult<(Decimal, Option<Addr>)> {
+    let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(&config.factory)?.to_string(),
+        msg: to_binary(&FactoryQueryMsg::Config {})?,
+    });
+    let factory_config: FactoryConfigResponse = match deps.querier.raw_query(&to_vec(&request)?) {
+        SystemResult::Err(SystemError::NoSuchContract { .. }) => {
+            return Ok((Decimal::zero(), None))
+        }
+        SystemResult::Err(system_err) => {
+            return Err(StdError::generic_err(format!(
+                "Querier system error: {}",
+                system_err
+            )))
+        }
+        SystemResult::Ok(ContractResult::Err(contract_err)) => {
+            return Err(StdError::generic_err(format!(
+                "Querier contract error: {}",
+                contract_err
+            )))
+        }
+        SystemResult::Ok(ContractResult::Ok(value)) => from_binary(&value)?,
+    };
+
+    match factory_config.fee_collector {
+        Some(fee_collector) => Ok((
+            factory_config.protocol_fee_share,
+            Some(deps.api.addr_validate(&fee_collector)?),
+        )),
+        None => Ok((Decimal::zero(), None)),
+    }
+}
+
+/// Split of a single sided deposit into an internal swap and a balanced provision
+struct SingleSidedProvision {
+    swap_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+    commission_amount: Uint128,
+    protocol_fee_amount: Uint128,
+    fee_collector: Option<Addr>,
+    share: Uint128,
+}
+
+/// Binary searches the swap amount after which the rest of the deposit
+/// and the swap return have the ratio of the pools after the swap
+fn compute_single_sided_provision(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset; 2],
+    offer_index: usize,
+    amount: Uint128,
+    total_share: Uint128,
+) -> Result<SingleSidedProvision, ContractError> {
+    if pools[0].amount.is_zero() || pools[1].amount.is_zero() || total_share.is_zero() {
+        return Err(StdError::generic_err("pool is empty, provide both assets first").into());
+    }
+
+    let offer_pool = pools[offer_index].amount;
+    let ask_pool = pools[1 - offer_index].amount;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let decimals = swap_decimals(&pair_info, offer_index);
+    let (protocol_fee_share, fee_collector) = query_protocol_fee_share(deps, config)?;
+
+    let mut low: u128 = 0;
+    let mut high: u128 = amount.u128();
+    while low < high {
+        let mid = Uint128::from(low + (high - low) / 2);
+        let (return_amount, _, commission_amount) =
+            compute_swap_by_type(deps, env, config, offer_pool, ask_pool, mid, decimals)?;
+        let new_ask_pool = ask_pool
+            .checked_sub(return_amount)?
+            .checked_sub(commission_amount * protocol_fee_share)?;
+
+        // (amount - mid) / (offer_pool + mid) > return_amount / new_ask_pool
+        // means too little is swapped
+        if Uint256::from(amount.checked_sub(mid)?) * Uint256::from(new_ask_pool)
+            > Uint256::from(return_amount) * Uint256::from(offer_pool.checked_add(mid)?)
+        {
+            low = mid.u128() + 1;
+        } else {
+            high = mid.u128();
+        }
+    }
+
+    let swap_amount = Uint128::from(low);
+    let (return_amount, spread_amount, commission_amount) = compute_swap_by_type(
+        deps,
+        env,
+        config,
+        offer_pool,
+        ask_pool,
+        swap_amount,
+        decimals,
+    )?;
+    let protocol_fee_amount = commission_amount * protocol_fee_share;
+
+    let new_offer_pool = offer_pool.checked_add(swap_amount)?;
+    let new_ask_pool = ask_pool
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+    let share = std::cmp::min(
+        amount
+            .checked_sub(swap_amount)?
+            .multiply_ratio(total_share, new_offer_pool),
+        return_amount.multiply_ratio(total_share, new_ask_pool),
+    );
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(SingleSidedProvision {
+        swap_amount,
+        return_amount,
+        spread_amount,
+        commission_amount,
+        protocol_fee_amount,
+        fee_collector,
+        share,
+    })
+}
+
+fn new_price_cumulative(block_time: u64) -> PriceCumulative {
+    PriceCumulative {
+        price0_cumulative_last: Decimal256::zero(),
+        price1_cumulative_last: Decimal256::zero(),
+        block_time_last: block_time,
+        observations_count: 0,
+    }
+}
+
+/// Prices of the first asset in the second one and the inverse, the ratio of the pools
+/// for xyk pairs and the marginal price of the curve for stable pairs
+fn compute_spot_prices(
+    storage: &dyn Storage,
+    pools: &[Asset; 2],
+    block_time: u64,
+) -> StdResult<(Decimal256, Decimal256)> {
+    let config: Config = CONFIG.load(storage)?;
+    match config.pair_type {
+        PairType::Xyk => {
+            let pool0: Uint256 = pools[0].amount.into();
+            let pool1: Uint256 = pools[1].amount.into();
+            Ok((
+                Decimal256::from_ratio(pool1, pool0),
+                Decimal256::from_ratio(pool0, pool1),
+            ))
+        }
+        PairType::Stable => {
+            let amp_config: AmpConfig = AMP_CONFIG.load(storage)?;
+            let pair_info: PairInfoRaw = PAIR_INFO.load(storage)?;
+            Ok(stableswap::compute_spot_prices(
+                compute_current_amp(&amp_config, block_time),
+                [pools[0].amount, pools[1].amount],
+                pair_info.asset_decimals,
+            ))
+        }
+    }
+}
+
+/// Extends the accumulators up to `block_time` with the given pools,
+/// empty pools do not contribute to the prices
+fn compute_cumulative_prices(
+    storage: &dyn Storage,
+    price_cumulative: &PriceCumulative,
+    pools: &[Asset; 2],
+    block_time: u64,
+) -> StdResult<(Decimal256, Decimal256)> {
+    let mut price0_cumulative = price_cumulative.price0_cumulative_last;
+    let mut price1_cumulative = price_cumulative.price1_cumulative_last;
+
+    if block_time > price_cumulative.block_time_last
+        && !pools[0].amount.is_zero()
+        && !pools[1].amount.is_zero()
+    {
+        let time_elapsed =
+            Decimal256::from_uint256(Uint256::from(block_time - price_cumulative.block_time_last));
+        // the prices over the elapsed time are the ones of the pools before the current action
+        let (price0, price1) = compute_spot_prices(storage, pools, block_time)?;
+
+        price0_cumulative += price0 * time_elapsed;
+        price1_cumulative += price1 * time_elapsed;
+    }
+
+    Ok((price0_cumulative, price1_cumulative))
+}
+
+/// Must be called with the pools before they are changed by the current action.
+/// Only the first action of a block moves the accumulators, and a snapshot is
+/// recorded in the ring buffer at most once per `OBSERVATION_PERIOD`
+fn accumulate_prices(
+    storage: &mut dyn Storage,
+    block_time: u64,
+    pools: &[Asset; 2],
+) -> StdResult<()> {
+    // legacy pairs start accumulating from their first action
+    let mut price_cumulative = PRICE_CUMULATIVE
+        .may_load(storage)?
+        .unwrap_or_else(|| new_price_cumulative(block_time));
+
+    let (price0_cumulative, price1_cumulative) =
+        compute_cumulative_prices(storage, &price_cumulative, pools, block_time)?;
+    price_cumulative.price0_cumulative_last = price0_cumulative;
+    price_cumulative.price1_cumulative_last = price1_cumulative;
+    price_cumulative.block_time_last = block_time;
+
+    let record_observation = if price_cumulative.observations_count == 0 {
+        true
+    } else {
+        let last_index = (price_cumulative.observations_count - 1) % OBSERVATIONS_SIZE;
+        let last_observation = OBSERVATIONS.load(storage, U64Key::new(last_index))?;
+        block_time >= last_observation.timestamp + OBSERVATION_PERIOD
+    };
+
+    if record_observation {
+        let index = price_cumulative.observations_count % OBSERVATIONS_SIZE;
+        OBSERVATIONS.save(
+            storage,
+            U64Key::new(index),
+            &Observation {
+                timestamp: block_time,
+                price0_cumulative,
+                price1_cumulative,
+            },
+        )?;
+        price_cumulative.observations_count += 1;
+    }
+
+    PRICE_CUMULATIVE.save(storage, &price_cumulative)
+}
+
+/// Largest deposits within `deposits` that have the ratio of the pools
+fn compute_optimal_deposits(deposits: &[Uint128; 2], pools: &[Asset; 2]) -> [Uint128; 2] {
+    let optimal_deposit_1 = deposits[0].multiply_ratio(pools[1].amount, pools[0].amount);
+    if optimal_deposit_1 <= deposits[1] {
+        [deposits[0], optimal_deposit_1]
+    } else {
+        [
+            deposits[1].multiply_ratio(pools[0].amount, pools[1].amount),
+            deposits[1],
+        ]
+    }
+}
+
+/// Share of the pools paid out for burning `amount` of `total_share`
+fn compute_refund_assets(
+    pools: &[Asset; 2],
+    amount: Uint128,
+    total_share: Uint128,
+) -> Result<[Asset; 2], ContractError> {
+    if total_share.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "no liquidity to withdraw",
+        )));
+    }
+
+    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+    Ok([
+        Asset {
+            info: pools[0].info.clone(),
+            amount: pools[0].amount * share_ratio,
+        },
+        Asset {
+            info: pools[1].info.clone(),
+            amount: pools[1].amount * share_ratio,
+        },
+    ])
+}
+
 /// If `belief_price` and `max_spread` both are given,
 /// we compute new spread else we just use terraswap
 /// spread to check `max_spread`
@@ -694,7 +2133,76 @@ fn assert_slippage_tolerance(
     Ok(())
 }
 
+/// Messages carrying a `deadline` fail once the block time is past it
+fn assert_deadline(block_time: u64, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if block_time > deadline {
+            return Err(ContractError::Expired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Each refund must be at least the `min_assets` entry of the same asset
+fn assert_min_assets(
+    refund_assets: &[Asset; 2],
+    min_assets: Option<[Asset; 2]>,
+) -> Result<(), ContractError> {
+    if let Some(min_assets) = min_assets {
+        for min_asset in min_assets.iter() {
+            let refund_asset = refund_assets
+                .iter()
+                .find(|a| a.info == min_asset.info)
+                .ok_or(ContractError::AssetMismatch {})?;
+
+            if refund_asset.amount < min_asset.amount {
+                return Err(ContractError::MinAssetsAssertion {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Commission must be strictly less than 1,
+/// otherwise the reverse simulation divides by zero
+fn assert_commission_rate(commission_rate: Decimal) -> StdResult<()> {
+    if commission_rate >= Decimal::one() {
+        return Err(StdError::generic_err("commission_rate must be less than 1"));
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored_version = assert_migrate_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // pairs created before the config was stored are xyk pairs with the default commission
+    if stored_version.is_none() && CONFIG.may_load(deps.storage)?.is_none() {
+        let factory = msg
+            .factory
+            .ok_or_else(|| StdError::generic_err("factory is required to migrate a legacy pair"))?;
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                factory: deps.api.addr_canonicalize(&factory)?,
+                commission_rate: Decimal::from_str(DEFAULT_COMMISSION_RATE)?,
+                pair_type: PairType::Xyk,
+            },
+        )?;
+    }
+
+    // pairs created before the reserves were stored start from their balances
+    if RESERVES.may_load(deps.storage)?.is_none() {
+        let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+        let pools: [Asset; 2] =
+            pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+        RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "migrate"), ("version", CONTRACT_VERSION)]))
 }
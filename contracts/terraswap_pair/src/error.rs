@@ -1,6 +1,8 @@
 use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
+use crate::contract::MINIMUM_LIQUIDITY_AMOUNT;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -18,12 +20,36 @@ pub enum ContractError {
     #[error("Max spread assertion")]
     MaxSpreadAssertion {},
 
+    #[error("Max offer amount assertion")]
+    MaxOfferAmountAssertion {},
+
+    #[error("Ask amount cannot be reached, lower the ask amount")]
+    AskAmountUnreachable {},
+
     #[error("Max slippage assertion")]
     MaxSlippageAssertion {},
 
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Min assets assertion")]
+    MinAssetsAssertion {},
+
+    #[error("Expired deadline")]
+    Expired {},
+
+    #[error("Flash swap in progress")]
+    FlashSwapInProgress {},
+
+    #[error("Constant product decreased by the flash swap")]
+    FlashSwapInvariantViolation {},
+
     #[error("Asset mismatch")]
     AssetMismatch {},
 
     #[error("Too small offer amount")]
     TooSmallOfferAmount {},
+
+    #[error("Pair is paused")]
+    Paused {},
 }
@@ -3,6 +3,7 @@ pub mod state;
 
 mod error;
 mod response;
+mod stableswap;
 
 #[cfg(test)]
 mod testing;
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+use terraswap::factory::{ConfigResponse as FactoryConfigResponse, QueryMsg as FactoryQueryMsg};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -27,6 +28,7 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
+    factory_querier: FactoryQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -82,6 +84,25 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
     owner_map
 }
 
+#[derive(Clone, Default)]
+pub struct FactoryQuerier {
+    protocol_fee_share: Decimal,
+    fee_collector: Option<String>,
+    unavailable: bool,
+    failing: bool,
+}
+
+impl FactoryQuerier {
+    pub fn new(protocol_fee_share: Decimal, fee_collector: Option<String>) -> Self {
+        FactoryQuerier {
+            protocol_fee_share,
+            fee_collector,
+            unavailable: false,
+            failing: false,
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -126,8 +147,31 @@ impl WasmMockQuerier {
                     panic!("DO NOT ENTER HERE")
                 }
             }
-            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
-                match from_binary(msg).unwrap() {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
+                Ok(FactoryQueryMsg::Config {}) if self.factory_querier.unavailable => {
+                    SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.to_string(),
+                    })
+                }
+                Ok(FactoryQueryMsg::Config {}) if self.factory_querier.failing => {
+                    SystemResult::Ok(ContractResult::Err("factory error".to_string()))
+                }
+                Ok(FactoryQueryMsg::Config {}) => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&FactoryConfigResponse {
+                        owner: "owner0000".to_string(),
+                        pair_code_id: 321u64,
+                        token_code_id: 123u64,
+                        commission_rate: Decimal::permille(3),
+                        protocol_fee_share: self.factory_querier.protocol_fee_share,
+                        fee_collector: self.factory_querier.fee_collector.clone(),
+                        permissioned: false,
+                        creation_fee: None,
+                        treasury: None,
+                        pair_types: vec![],
+                    })
+                    .unwrap(),
+                )),
+                _ => match from_binary(msg).unwrap() {
                     Cw20QueryMsg::TokenInfo {} => {
                         let balances: &HashMap<String, Uint128> =
                             match self.token_querier.balances.get(contract_addr) {
@@ -191,8 +235,8 @@ impl WasmMockQuerier {
                         ))
                     }
                     _ => panic!("DO NOT ENTER HERE"),
-                }
-            }
+                },
+            },
             _ => self.base.handle_query(request),
         }
     }
@@ -204,6 +248,7 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
+            factory_querier: FactoryQuerier::default(),
         }
     }
 
@@ -217,6 +262,25 @@ impl WasmMockQuerier {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
 
+    // configure the protocol fee of the factory
+    pub fn with_protocol_fee(
+        &mut self,
+        protocol_fee_share: Decimal,
+        fee_collector: Option<String>,
+    ) {
+        self.factory_querier = FactoryQuerier::new(protocol_fee_share, fee_collector);
+    }
+
+    // remove the factory contract, as for a pair instantiated without factory
+    pub fn with_factory_unavailable(&mut self) {
+        self.factory_querier.unavailable = true;
+    }
+
+    // make the factory config query fail
+    pub fn with_factory_error(&mut self) {
+        self.factory_querier.failing = true;
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.clone());
@@ -0,0 +1,261 @@
+use crate::error::ContractError;
+use crate::state::AmpConfig;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
+
+/// Upper bound of the amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// A single ramp can change the amplification coefficient at most by this factor
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Minimum duration of a ramp in seconds
+pub const MIN_RAMP_TIME: u64 = 86400;
+
+/// Newton's method iterations before giving up on convergence
+const ITERATIONS: u8 = 255;
+
+pub fn assert_amp(amp: u64) -> StdResult<()> {
+    if amp == 0 || amp > MAX_AMP {
+        return Err(StdError::generic_err(format!(
+            "amp must be between 1 and {}",
+            MAX_AMP
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the amplification coefficient at `block_time`
+pub fn compute_current_amp(amp_config: &AmpConfig, block_time: u64) -> u64 {
+    if block_time >= amp_config.next_amp_time {
+        return amp_config.next_amp;
+    }
+
+    let elapsed_time = block_time - amp_config.init_amp_time;
+    let ramp_time = amp_config.next_amp_time - amp_config.init_amp_time;
+    if amp_config.next_amp > amp_config.init_amp {
+        amp_config.init_amp + (amp_config.next_amp - amp_config.init_amp) * elapsed_time / ramp_time
+    } else {
+        amp_config.init_amp - (amp_config.init_amp - amp_config.next_amp) * elapsed_time / ramp_time
+    }
+}
+
+/// Computes the StableSwap invariant `D` of a two assets pool
+/// `A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)`
+fn compute_d(amp: u64, pools: [Uint256; 2]) -> Uint256 {
+    let sum = pools[0] + pools[1];
+    if sum.is_zero() {
+        return Uint256::zero();
+    }
+
+    // A * n^n
+    let ann = Uint256::from(amp * 4);
+    let mut d = sum;
+    for _ in 0..ITERATIONS {
+        // D^(n+1) / (n^n * x * y)
+        let d_p = d
+            .multiply_ratio(d, pools[0] * Uint256::from(2u64))
+            .multiply_ratio(d, pools[1] * Uint256::from(2u64));
+        let d_prev = d;
+        d = (ann * sum + d_p * Uint256::from(2u64))
+            .multiply_ratio(d, (ann - Uint256::one()) * d + d_p * Uint256::from(3u64));
+
+        if abs_diff(d, d_prev) <= Uint256::one() {
+            break;
+        }
+    }
+
+    d
+}
+
+/// Computes the pool of the other asset which keeps `D`
+/// when the pool of one asset becomes `new_pool`
+fn compute_y(amp: u64, new_pool: Uint256, d: Uint256) -> Uint256 {
+    let ann = Uint256::from(amp * 4);
+
+    // c = D^(n+1) / (n^n * new_pool * A * n^n)
+    let c = d
+        .multiply_ratio(d, new_pool * Uint256::from(2u64))
+        .multiply_ratio(d, ann * Uint256::from(2u64));
+    // b = new_pool + D / (A * n^n)
+    let b = new_pool + d.multiply_ratio(Uint256::one(), ann);
+
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        let y_prev = y;
+        y = (y * y + c).multiply_ratio(Uint256::one(), y * Uint256::from(2u64) + b - d);
+
+        if abs_diff(y, y_prev) <= Uint256::one() {
+            break;
+        }
+    }
+
+    y
+}
+
+/// Multipliers bringing the amounts of both assets
+/// to the precision of the asset with more decimals
+fn precision_factors(decimals: [u8; 2]) -> [Uint256; 2] {
+    let precision = decimals[0].max(decimals[1]);
+    let factor = |decimals: u8| {
+        (decimals..precision).fold(Uint256::one(), |factor, _| factor * Uint256::from(10u64))
+    };
+
+    [factor(decimals[0]), factor(decimals[1])]
+}
+
+/// Invariant `D` of the pools at a common precision, a swap must not decrease it
+pub fn compute_invariant(amp: u64, pools: [Uint128; 2], decimals: [u8; 2]) -> Uint256 {
+    let factors = precision_factors(decimals);
+    compute_d(
+        amp,
+        [
+            Uint256::from(pools[0]) * factors[0],
+            Uint256::from(pools[1]) * factors[1],
+        ],
+    )
+}
+
+/// Marginal prices of the curve, the price of the first asset in the second one and the inverse.
+/// With `r = D^(n+1) / (n^n * x * y)`, the price of `x` in `y` is
+/// `(A * n^n * x + r) / (A * n^n * y + r) * y / x`, it is `y / x` for a zero `A` and `1` for an infinite one
+pub fn compute_spot_prices(
+    amp: u64,
+    pools: [Uint128; 2],
+    decimals: [u8; 2],
+) -> (Decimal256, Decimal256) {
+    let factors = precision_factors(decimals);
+    let x = Uint256::from(pools[0]) * factors[0];
+    let y = Uint256::from(pools[1]) * factors[1];
+
+    let ann = Uint256::from(amp * 4);
+    let d = compute_d(amp, [x, y]);
+    let r = d
+        .multiply_ratio(d, x * Uint256::from(2u64))
+        .multiply_ratio(d, y * Uint256::from(2u64));
+
+    // the factors cancel out in the ratio of the pools
+    let pool0: Uint256 = pools[0].into();
+    let pool1: Uint256 = pools[1].into();
+    (
+        Decimal256::from_ratio(ann * x + r, ann * y + r) * Decimal256::from_ratio(pool1, pool0),
+        Decimal256::from_ratio(ann * y + r, ann * x + r) * Decimal256::from_ratio(pool0, pool1),
+    )
+}
+
+fn abs_diff(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// StableSwap version of `compute_swap`, the spread is measured against the 1:1 peg.
+/// `decimals` are the decimals of the offer and the ask asset, the pools are compared
+/// at the precision of the asset with more decimals
+pub fn compute_swap(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+    decimals: [u8; 2],
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(StdError::generic_err("pool is empty").into());
+    }
+
+    let factors = precision_factors(decimals);
+    let offer_pool: Uint256 = Uint256::from(offer_pool) * factors[0];
+    let ask_pool: Uint256 = Uint256::from(ask_pool) * factors[1];
+    let offer_amount: Uint256 = Uint256::from(offer_amount) * factors[0];
+    let commission_rate: Decimal256 = commission_rate.into();
+
+    let d = compute_d(amp, [offer_pool, ask_pool]);
+    let new_ask_pool = compute_y(amp, offer_pool + offer_amount, d);
+
+    // round down in favor of the pool
+    let return_amount = if ask_pool > new_ask_pool + Uint256::one() {
+        ask_pool - new_ask_pool - Uint256::one()
+    } else {
+        Uint256::zero()
+    };
+
+    // calculate spread & commission in the ask asset
+    let spread_amount = if offer_amount > return_amount {
+        (offer_amount - return_amount).multiply_ratio(Uint256::one(), factors[1])
+    } else {
+        Uint256::zero()
+    };
+    let return_amount = return_amount.multiply_ratio(Uint256::one(), factors[1]);
+    let commission_amount: Uint256 = return_amount * commission_rate;
+
+    // commission will be absorbed to pool
+    let return_amount: Uint256 = return_amount - commission_amount;
+    Ok((
+        return_amount.into(),
+        spread_amount.into(),
+        commission_amount.into(),
+    ))
+}
+
+/// StableSwap version of `compute_offer_amount`, the spread is measured against the 1:1 peg.
+/// `decimals` are the decimals of the offer and the ask asset
+pub fn compute_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+    decimals: [u8; 2],
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(StdError::generic_err("pool is empty").into());
+    }
+
+    let ask_pool: Uint256 = ask_pool.into();
+    let ask_amount: Uint256 = ask_amount.into();
+    let commission_rate: Decimal256 = commission_rate.into();
+
+    let one_minus_commission = Decimal256::one() - commission_rate;
+    let inv_one_minus_commission = Decimal256::one() / one_minus_commission;
+    let before_commission_deduction: Uint256 = ask_amount * inv_one_minus_commission;
+    if before_commission_deduction >= ask_pool {
+        return Err(StdError::generic_err("ask amount exceeds the pool").into());
+    }
+
+    let factors = precision_factors(decimals);
+    let offer_pool: Uint256 = Uint256::from(offer_pool) * factors[0];
+    let ask_pool: Uint256 = ask_pool * factors[1];
+    let d = compute_d(amp, [offer_pool, ask_pool]);
+    let new_offer_pool = compute_y(amp, ask_pool - before_commission_deduction * factors[1], d);
+
+    // round up in favor of the pool
+    let offer_amount = new_offer_pool + Uint256::one() - offer_pool;
+
+    // the spread in the ask asset
+    let spread_amount = if offer_amount > before_commission_deduction * factors[1] {
+        (offer_amount - before_commission_deduction * factors[1])
+            .multiply_ratio(Uint256::one(), factors[1])
+    } else {
+        Uint256::zero()
+    };
+    let offer_amount =
+        (offer_amount + factors[0] - Uint256::one()).multiply_ratio(Uint256::one(), factors[0]);
+
+    let commission_amount = before_commission_deduction * commission_rate;
+
+    // check small amount swap
+    if commission_amount.is_zero() {
+        return Err(ContractError::TooSmallOfferAmount {});
+    }
+
+    Ok((
+        offer_amount.into(),
+        spread_amount.into(),
+        commission_amount.into(),
+    ))
+}
@@ -1,9 +1,84 @@
-use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{CanonicalAddr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
 use terraswap::asset::PairInfoRaw;
+use terraswap::pair::PairType;
 
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const PAIR_INFO: Item<PairInfoRaw> = Item::new("\u{0}\u{9}pair_info");
 
+/// Pool reserves in the order of `PairInfoRaw::asset_infos`, tokens sent
+/// to the pair outside of its operations are not counted until `Sync`
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+/// Flash swap waiting for its reply, the pair is locked while it exists
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashSwapState {
+    pub borrower: CanonicalAddr,
+    /// Reserves before the flash swap
+    pub reserves: [Uint128; 2],
+    /// Amounts sent to the borrower, in the order of the reserves
+    pub borrowed: [Uint128; 2],
+}
+
+pub const FLASH_SWAP: Item<FlashSwapState> = Item::new("flash_swap");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// The factory is the only account allowed to update the pair parameters
+    pub factory: CanonicalAddr,
+    pub commission_rate: Decimal,
+    pub pair_type: PairType,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Amplification coefficient of a stable pair, it moves linearly
+/// from `init_amp` to `next_amp` between `init_amp_time` and `next_amp_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmpConfig {
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub next_amp: u64,
+    pub next_amp_time: u64,
+}
+
+pub const AMP_CONFIG: Item<AmpConfig> = Item::new("amp_config");
+
+/// Uniswap v2 style price accumulators, `price0` is the price of
+/// `asset_infos[0]` in `asset_infos[1]` and `price1` the inverse
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceCumulative {
+    pub price0_cumulative_last: Decimal256,
+    pub price1_cumulative_last: Decimal256,
+    pub block_time_last: u64,
+    /// Number of observations ever recorded, the next one is stored
+    /// at `observations_count % OBSERVATIONS_SIZE`
+    pub observations_count: u64,
+}
+
+pub const PRICE_CUMULATIVE: Item<PriceCumulative> = Item::new("price_cumulative");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub price0_cumulative: Decimal256,
+    pub price1_cumulative: Decimal256,
+}
+
+/// Ring buffer of accumulator snapshots used for TWAP queries
+pub const OBSERVATIONS: Map<U64Key, Observation> = Map::new("observations");
+
+/// Capacity of the observation ring buffer
+pub const OBSERVATIONS_SIZE: u64 = 120;
+
+/// Minimum spacing in seconds between two observations,
+/// so the buffer covers at least `OBSERVATIONS_SIZE * OBSERVATION_PERIOD` seconds
+pub const OBSERVATION_PERIOD: u64 = 60;
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -37,6 +112,9 @@ mod test {
                 ],
                 contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
                 liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                paused: false,
+                pair_type: "xyk".to_string(),
+                asset_decimals: [6, 6],
             },
         )
         .unwrap();
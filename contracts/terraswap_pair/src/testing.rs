@@ -1,23 +1,32 @@
 use crate::contract::{
-    assert_max_spread, execute, instantiate, query_pair_info, query_pool, query_reverse_simulation,
-    query_simulation, reply,
+    assert_max_spread, execute, instantiate, migrate, query_config, query_cumulative_prices,
+    query_pair_info, query_pool, query_reverse_simulation, query_simulate_provide_single_sided,
+    query_simulate_withdraw_liquidity, query_simulation, query_twap, reply,
 };
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
+use crate::state::{CONFIG, OBSERVATIONS_SIZE, OBSERVATION_PERIOD, RESERVES};
 
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply, ReplyOn, Response,
-    StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, to_binary, Api, BankMsg, CanonicalAddr, Coin, ContractResult, CosmosMsg, Decimal, Reply,
+    ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use cw_storage_plus::Item;
+use terraswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfo};
 use terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PairType, PoolResponse,
+    ReverseSimulationResponse, SimulateProvideSingleSidedResponse, SimulationResponse,
+    TwapResponse,
 };
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -32,6 +41,11 @@ fn proper_initialization() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 8],
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -56,7 +70,7 @@ fn proper_initialization() {
                 .unwrap(),
                 funds: vec![],
                 label: "".to_string(),
-                admin: None,
+                admin: Some("addr0000".to_string()),
             }
             .into(),
             gas_limit: None,
@@ -95,6 +109,7 @@ fn proper_initialization() {
             }
         ]
     );
+    assert_eq!(pair_info.asset_decimals, [6, 8]);
 }
 
 #[test]
@@ -122,6 +137,11 @@ fn provide_liquidity() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
     };
 
     let env = mock_env();
@@ -152,17 +172,18 @@ fn provide_liquidity() {
                 info: AssetInfo::Token {
                     contract_addr: "asset0000".to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: "uusd".to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -170,12 +191,13 @@ fn provide_liquidity() {
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(2000u128),
         }],
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let lock_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -183,7 +205,20 @@ fn provide_liquidity() {
             msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
                 owner: "addr0000".to_string(),
                 recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    // the minimum liquidity is locked in the pair
+    assert_eq!(
+        lock_msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(1000u128),
             })
             .unwrap(),
             funds: vec![],
@@ -195,24 +230,26 @@ fn provide_liquidity() {
             contract_addr: "liquidity0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(1000u128),
             })
             .unwrap(),
             funds: vec![],
         }))
     );
 
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(2000u128), Uint128::from(2000u128)]
+    );
+
     // provide more liquidity 1:2, which is not proportional to 1:1,
-    // then it must accept 1:1 and treat left amount as donation
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                200u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // then it must accept 1:1 and refund the excess
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(200u128), Uint128::from(200u128)],
+        )
+        .unwrap();
 
     deps.querier.with_token_balances(&[
         (
@@ -242,6 +279,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: Some("staking0000".to_string()), // try changing receiver
+        deadline: None,
     };
 
     let env = mock_env();
@@ -282,6 +320,25 @@ fn provide_liquidity() {
             funds: vec![],
         }))
     );
+    let refund_msg = res.messages.get(2).expect("no message");
+    assert_eq!(
+        refund_msg,
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        res.attributes.get(5).expect("no log"),
+        &attr("refund_assets", "100uusd, 0asset0000")
+    );
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(300u128), Uint128::from(300u128)]
+    );
 
     // check wrong argument
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -301,6 +358,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -320,16 +378,13 @@ fn provide_liquidity() {
         _ => panic!("Must return generic error"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 100u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     deps.querier.with_token_balances(&[
         (
@@ -360,6 +415,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -376,14 +432,13 @@ fn provide_liquidity() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 98u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -403,6 +458,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -419,16 +475,13 @@ fn provide_liquidity() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 100u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -448,6 +501,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -460,14 +514,13 @@ fn provide_liquidity() {
     );
     let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 99u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -487,6 +540,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -497,7 +551,28 @@ fn provide_liquidity() {
             amount: Uint128::from(99u128),
         }],
     );
-    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // only 99 of the token is taken
+    let transfer_from_msg = res.messages.first().expect("no message");
+    assert_eq!(
+        transfer_from_msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0001".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(99u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.attributes.get(5).expect("no log"),
+        &attr("refund_assets", "0uusd, 1asset0000")
+    );
 }
 
 #[test]
@@ -532,6 +607,11 @@ fn withdraw_liquidity() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
     };
 
     let env = mock_env();
@@ -555,14 +635,101 @@ fn withdraw_liquidity() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // withdraw liquidity
+    // the pools of the pair
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // simulate withdrawing half of the share
+    let res = query_simulate_withdraw_liquidity(deps.as_ref(), Uint128::from(50u128)).unwrap();
+    assert_eq!(
+        res.refund_assets,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(50u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(50u128),
+            },
+        ]
+    );
+
+    // refund below min_assets must fail
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some([
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::from(101u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+            ]),
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::MinAssetsAssertion {}));
+
+    // expired deadline must fail
+    let env = mock_env();
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            deadline: Some(env.block.time.seconds() - 1),
+        })
+        .unwrap(),
         amount: Uint128::from(100u128),
     });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(res, Err(ContractError::Expired {}));
 
+    // withdraw liquidity
     let env = mock_env();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some([
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+            ]),
+            deadline: Some(env.block.time.seconds()),
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+
     let info = mock_info("liquidity0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     let log_withdrawn_share = res.attributes.get(2).expect("no log");
@@ -612,6 +779,22 @@ fn withdraw_liquidity() {
         log_refund_assets,
         &attr("refund_assets", "100uusd, 100asset0000")
     );
+
+    // nothing can be withdrawn once the whole share is burnt
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+    ]);
+    let res = query_simulate_withdraw_liquidity(deps.as_ref(), Uint128::zero()).unwrap_err();
+    match res {
+        ContractError::Std(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "no liquidity to withdraw".to_string())
+        }
+        _ => panic!("Must return generic error"),
+    }
 }
 
 #[test]
@@ -653,6 +836,11 @@ fn try_native_to_token() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
     };
 
     let env = mock_env();
@@ -676,6 +864,14 @@ fn try_native_to_token() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     // normal swap
     let msg = ExecuteMsg::Swap {
         offer_asset: Asset {
@@ -687,6 +883,7 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env();
     let info = mock_info(
@@ -711,17 +908,26 @@ fn try_native_to_token() {
         .unwrap();
     let expected_tax_amount = Uint128::zero(); // no tax for token
 
-    // check simulation res
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
-        }],
-    )]);
+    // the offer is added to the pool and the return is removed from it
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [
+            collateral_pool_amount + offer_amount,
+            asset_pool_amount - expected_return_amount,
+        ]
+    );
+
+    // check simulation with the pools before the swap
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             info: AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
@@ -737,6 +943,7 @@ fn try_native_to_token() {
     // check reverse simulation res
     let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             info: AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
@@ -776,6 +983,7 @@ fn try_native_to_token() {
             attr("tax_amount", expected_tax_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("protocol_fee_amount", "0"),
         ]
     );
 
@@ -833,6 +1041,11 @@ fn try_token_to_native() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
     };
 
     let env = mock_env();
@@ -856,6 +1069,14 @@ fn try_token_to_native() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     // unauthorized access; can not execute swap directly for token swap
     let msg = ExecuteMsg::Swap {
         offer_asset: Asset {
@@ -867,6 +1088,7 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -884,6 +1106,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -913,20 +1136,17 @@ fn try_token_to_native() {
             .unwrap(),
     );
     // check simulation res
-    // return asset token balance as normal
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
-        ),
-    ]);
+    // with the pools before the swap
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             amount: offer_amount,
             info: AssetInfo::Token {
@@ -942,6 +1162,7 @@ fn try_token_to_native() {
     // check reverse simulation res
     let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
         deps.as_ref(),
+        mock_env(),
         Asset {
             amount: expected_return_amount,
             info: AssetInfo::NativeToken {
@@ -980,6 +1201,7 @@ fn try_token_to_native() {
             attr("tax_amount", expected_tax_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("protocol_fee_amount", "0"),
         ]
     );
 
@@ -1004,6 +1226,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1115,6 +1338,11 @@ fn test_query_pool() {
             },
         ],
         token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
     };
 
     let env = mock_env();
@@ -1138,6 +1366,19 @@ fn test_query_pool() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // the reserves are empty until they are synced with the balances
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
     let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
 
     assert_eq!(
@@ -1159,3 +1400,2387 @@ fn test_query_pool() {
     );
     assert_eq!(res.total_share, total_share_amount);
 }
+
+#[test]
+fn update_config() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    // the instantiator is regarded as the factory
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let config_res: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(
+        config_res,
+        ConfigResponse {
+            factory: "factory0000".to_string(),
+            commission_rate: Decimal::permille(3),
+            pair_type: PairType::Xyk,
+            amp: None,
+        }
+    );
+
+    // only the factory can update the config
+    let msg = ExecuteMsg::UpdateConfig {
+        commission_rate: Some(Decimal::percent(1)),
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // commission rate must be less than 1
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::UpdateConfig {
+            commission_rate: Some(Decimal::one()),
+        },
+    )
+    .unwrap_err();
+    match res {
+        ContractError::Std(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "commission_rate must be less than 1")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("commission_rate", "0.01"),
+        ]
+    );
+
+    let config_res: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(Decimal::percent(1), config_res.commission_rate);
+
+    // simulation applies the updated commission rate
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(1u128, 100u128); // 1%
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+    assert_eq!(
+        expected_ret_amount
+            .checked_sub(expected_commission_amount)
+            .unwrap(),
+        simulation_res.return_amount
+    );
+}
+
+#[test]
+fn swap_with_protocol_fee() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    // 10% of the commission goes to the collector
+    deps.querier
+        .with_protocol_fee(Decimal::percent(10), Some("collector0000".to_string()));
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env();
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+    let expected_protocol_fee_amount = expected_commission_amount * Decimal::percent(10);
+
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: expected_return_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "collector0000".to_string(),
+                    amount: expected_protocol_fee_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr(
+            "protocol_fee_amount",
+            expected_protocol_fee_amount.to_string()
+        )
+    );
+
+    // check simulation res
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(expected_return_amount, simulation_res.return_amount);
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+    assert_eq!(
+        expected_protocol_fee_amount,
+        simulation_res.protocol_fee_amount
+    );
+
+    // without fee collector the whole commission is absorbed to pool
+    deps.querier.with_protocol_fee(Decimal::percent(10), None);
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(Uint128::zero(), simulation_res.protocol_fee_amount);
+
+    // a failing factory query is not mistaken for a zero protocol fee
+    deps.querier
+        .with_protocol_fee(Decimal::percent(10), Some("collector0000".to_string()));
+    deps.querier.with_factory_error();
+    let offer = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: offer_amount,
+    };
+    match query_simulation(deps.as_ref(), mock_env(), offer.clone()) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Querier contract error: factory error")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a pair without factory contract still swaps, without protocol fee
+    deps.querier
+        .with_protocol_fee(Decimal::percent(10), Some("collector0000".to_string()));
+    deps.querier.with_factory_unavailable();
+    let simulation_res: SimulationResponse =
+        query_simulation(deps.as_ref(), mock_env(), offer).unwrap();
+    assert_eq!(Uint128::zero(), simulation_res.protocol_fee_amount);
+}
+
+#[test]
+fn twap() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let mut env = mock_env();
+    let start_time = env.block.time.seconds();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+
+    // the pools are reset to (collateral, asset) after every swap
+    let price0 = Decimal256::from_ratio(
+        Uint256::from(asset_pool_amount),
+        Uint256::from(collateral_pool_amount),
+    );
+    let price1 = Decimal256::from_ratio(
+        Uint256::from(collateral_pool_amount),
+        Uint256::from(asset_pool_amount),
+    );
+
+    // the first swap accumulates 100 seconds and records the first observation
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // the second swap is too close to the last observation to record a new one
+    env.block.time = env.block.time.plus_seconds(30);
+    execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(70);
+    let res = query_cumulative_prices(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(res.block_time_last, start_time + 200);
+    assert_eq!(
+        res.price0_cumulative_last,
+        price0 * Decimal256::from_uint256(Uint256::from(100u64))
+            + price0 * Decimal256::from_uint256(Uint256::from(30u64))
+            + price0 * Decimal256::from_uint256(Uint256::from(70u64))
+    );
+    assert_eq!(
+        res.price1_cumulative_last,
+        price1 * Decimal256::from_uint256(Uint256::from(100u64))
+            + price1 * Decimal256::from_uint256(Uint256::from(30u64))
+            + price1 * Decimal256::from_uint256(Uint256::from(70u64))
+    );
+
+    let res: TwapResponse = query_twap(deps.as_ref(), env.clone(), 50).unwrap();
+    assert_eq!(
+        res,
+        TwapResponse {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+            ],
+            price0_average: price0,
+            price1_average: price1,
+            from_time: start_time + 100,
+            to_time: start_time + 200,
+        }
+    );
+
+    // no observation is older than the first swap
+    match query_twap(deps.as_ref(), env.clone(), 150) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "no observation is old enough")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match query_twap(deps.as_ref(), env.clone(), 0) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "seconds_ago must be bigger than zero")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // fill the ring buffer, the oldest observations are overwritten
+    for _ in 0..OBSERVATIONS_SIZE {
+        env.block.time = env.block.time.plus_seconds(OBSERVATION_PERIOD);
+        execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+        RESERVES
+            .save(
+                &mut deps.storage,
+                &[collateral_pool_amount, asset_pool_amount],
+            )
+            .unwrap();
+    }
+
+    let oldest_time = env.block.time.seconds() - (OBSERVATIONS_SIZE - 1) * OBSERVATION_PERIOD;
+    let res: TwapResponse = query_twap(
+        deps.as_ref(),
+        env.clone(),
+        (OBSERVATIONS_SIZE - 1) * OBSERVATION_PERIOD,
+    )
+    .unwrap();
+    assert_eq!(res.from_time, oldest_time);
+    assert_eq!(res.price0_average, price0);
+
+    match query_twap(
+        deps.as_ref(),
+        env,
+        (OBSERVATIONS_SIZE - 1) * OBSERVATION_PERIOD + 1,
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "no observation is old enough")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn stable_swap() {
+    let total_share = Uint128::from(2000000000000u128);
+    let asset_pool_amount = Uint128::from(1000000000000u128);
+    let collateral_pool_amount = Uint128::from(1000000000000u128);
+    let offer_amount = Uint128::from(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let mut msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable),
+        pair_type_name: Some("stable-0.1".to_string()),
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    // amp is required for stable pairs
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    match instantiate(deps.as_mut(), env.clone(), info.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "amp is required for stable pairs")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    msg.amp = Some(100u64);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the pair keeps the name of its type in the factory
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(pair_info.pair_type, "stable-0.1");
+
+    // the pools before the user deposit
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(1)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the constant product curve would return 996,003,997
+    let expected_return_amount = Uint128::from(996_995_039u128);
+    let expected_spread_amount = Uint128::from(4_976u128);
+    let expected_commission_amount = Uint128::from(2_999_985u128);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: expected_return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // check simulation res
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(expected_return_amount, simulation_res.return_amount);
+    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: expected_return_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(offer_amount, reverse_simulation_res.offer_amount);
+    assert_eq!(expected_spread_amount, reverse_simulation_res.spread_amount);
+    assert_eq!(
+        expected_commission_amount,
+        reverse_simulation_res.commission_amount
+    );
+}
+
+#[test]
+fn stable_swap_with_different_decimals() {
+    let total_share = Uint128::from(2000000000000u128);
+    // one million of each asset, with 6 and 18 decimals
+    let collateral_pool_amount = Uint128::from(1_000_000_000_000u128);
+    let asset_pool_amount = Uint128::from(1_000_000_000_000_000_000_000_000u128);
+    let offer_amount = Uint128::from(1_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable),
+        pair_type_name: None,
+        amp: Some(100u64),
+        asset_decimals: [6, 18],
+    };
+
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // one uusd returns about one asset less the commission of 0.3%
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert!(simulation_res.return_amount > Uint128::from(996_990_000_000_000_000u128));
+    assert!(simulation_res.return_amount < Uint128::from(997_000_000_000_000_000u128));
+    assert!(simulation_res.commission_amount > Uint128::from(2_990_000_000_000_000u128));
+    assert!(simulation_res.spread_amount < Uint128::from(1_000_000_000_000u128));
+
+    // the reverse simulation asks for the same offer in uusd
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: simulation_res.return_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(reverse_simulation_res.offer_amount, offer_amount);
+
+    // and the other way around
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1_000_000_000_000_000_000u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(simulation_res.return_amount, Uint128::from(997_000u128));
+}
+
+#[test]
+fn stable_twap() {
+    let total_share = Uint128::from(3000000000000u128);
+    let collateral_pool_amount = Uint128::from(2000000000000u128);
+    let asset_pool_amount = Uint128::from(1000000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable),
+        pair_type_name: None,
+        amp: Some(100u64),
+        asset_decimals: [6, 6],
+    };
+
+    let mut env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // the prices follow the curve, not the 2:1 ratio of the pools
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = query_cumulative_prices(deps.as_ref(), env).unwrap();
+    let elapsed = Decimal256::from_uint256(Uint256::from(100u64));
+    let price0 = res.price0_cumulative_last / elapsed;
+    let price1 = res.price1_cumulative_last / elapsed;
+    assert!(price0 > Decimal256::percent(95) && price0 < Decimal256::one());
+    assert!(price1 > Decimal256::one() && price1 < Decimal256::percent(105));
+}
+
+#[test]
+fn ramp_amp() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000000000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1000000000000u128),
+        )],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable),
+        pair_type_name: None,
+        amp: Some(100u64),
+        asset_decimals: [6, 6],
+    };
+
+    let mut env = mock_env();
+    let start_time = env.block.time.seconds();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the pools of the pair
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[
+                Uint128::from(1000000000000u128),
+                Uint128::from(1000000000000u128),
+            ],
+        )
+        .unwrap();
+
+    let config_res: ConfigResponse = query_config(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(config_res.pair_type, PairType::Stable);
+    assert_eq!(config_res.amp, Some(100u64));
+
+    // only the factory can ramp amp
+    let msg = ExecuteMsg::RampAmp {
+        future_amp: 10u64,
+        future_time: start_time + 2 * 86400,
+    };
+    let info = mock_info("addr0000", &[]);
+    match execute(deps.as_mut(), env.clone(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // too short ramp
+    let info = mock_info("factory0000", &[]);
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RampAmp {
+            future_amp: 10u64,
+            future_time: start_time + 3600,
+        },
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "amp ramp must last at least 86400 seconds")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // too big change
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RampAmp {
+            future_amp: 1001u64,
+            future_time: start_time + 2 * 86400,
+        },
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "amp can change at most 10 times in a ramp")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "ramp_amp"),
+            attr("init_amp", "100"),
+            attr("future_amp", "10"),
+            attr("future_time", (start_time + 2 * 86400).to_string()),
+        ]
+    );
+
+    // amp moves linearly
+    env.block.time = env.block.time.plus_seconds(86400);
+    let config_res: ConfigResponse = query_config(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(config_res.amp, Some(55u64));
+
+    env.block.time = env.block.time.plus_seconds(2 * 86400);
+    let config_res: ConfigResponse = query_config(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(config_res.amp, Some(10u64));
+
+    // the simulation follows the ramped amp
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        env.clone(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000000u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(simulation_res.return_amount, Uint128::from(996_952_526u128));
+
+    // stop in the middle of a ramp
+    let ramp_start_time = env.block.time.seconds();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RampAmp {
+            future_amp: 20u64,
+            future_time: ramp_start_time + 100000,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("init_amp", "10"));
+
+    env.block.time = env.block.time.plus_seconds(50000);
+    let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::StopRampAmp {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "stop_ramp_amp"), attr("amp", "15")]
+    );
+
+    env.block.time = env.block.time.plus_seconds(50000);
+    let config_res: ConfigResponse = query_config(deps.as_ref(), env).unwrap();
+    assert_eq!(config_res.amp, Some(15u64));
+}
+
+#[test]
+fn skim_and_sync() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(120u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // 50 uusd and 20 asset were donated to the pair
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150u128),
+        }],
+    )]);
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Skim {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(20u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("sender", "addr0001"),
+            attr("skimmed_assets", "50uusd, 20asset0000"),
+        ]
+    );
+
+    // the reserves are not changed by skim
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(100u128)]
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Sync {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "150uusd, 120asset0000"),
+        ]
+    );
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::from(150u128));
+    assert_eq!(res.assets[1].amount, Uint128::from(120u128));
+}
+
+#[test]
+fn expired_deadline() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let env = mock_env();
+    let deadline = Some(env.block.time.seconds() - 1);
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res, Err(ContractError::Expired {}));
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res, Err(ContractError::Expired {}));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(res, Err(ContractError::Expired {}));
+}
+
+#[test]
+fn provide_single_sided() {
+    let total_share = Uint128::from(1000000u128);
+    let pool_amount = Uint128::from(1000000u128);
+    let offer_amount = Uint128::from(100000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount + offer_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+
+    // 10% of the commission goes to the collector
+    deps.querier
+        .with_protocol_fee(Decimal::percent(10), Some("collector0000".to_string()));
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // cannot provide single sided to empty pools
+    let res = query_simulate_provide_single_sided(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "pool is empty, provide both assets first")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    let simulation = query_simulate_provide_single_sided(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+
+    // 48886uusd is swapped to 46468asset0000 so that the rest 51114uusd
+    // matches the ratio of the pools after the swap, 1048886uusd and 953519asset0000
+    assert_eq!(
+        simulation,
+        SimulateProvideSingleSidedResponse {
+            swap_amount: Uint128::from(48886u128),
+            return_amount: Uint128::from(46468u128),
+            spread_amount: Uint128::from(2279u128),
+            commission_amount: Uint128::from(139u128),
+            share: Uint128::from(48731u128),
+        }
+    );
+
+    // the spread of the internal swap exceeds the tolerance
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        slippage_tolerance: Some(Decimal::percent(1)),
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::MaxSpreadAssertion {}));
+
+    // only the pool tokens can provide through the hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::ProvideSingleSided {
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(48731u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "collector0000".to_string(),
+                    amount: Uint128::from(13u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(res.attributes[4], attr("swap_amount", "48886"));
+    assert_eq!(res.attributes[9], attr("share", "48731"));
+
+    // the whole deposit stays in the pool, only the protocol fee leaves
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(1100000u128), Uint128::from(999987u128)]
+    );
+}
+
+#[test]
+fn minimum_liquidity() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let provide_msg = |amount: u128| ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(amount),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(amount),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+
+    // the first deposit must mint more than the locked share
+    let info = mock_info(
+        "attacker0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, provide_msg(1000));
+    assert_eq!(res, Err(ContractError::MinimumLiquidityAmountError {}));
+
+    // the attacker provides the smallest first deposit and gets 1 share
+    let info = mock_info(
+        "attacker0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1001u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, provide_msg(1001)).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(res.attributes[4], attr("share", "1"));
+
+    // then donates 1_000_000 of each asset and syncs the reserves
+    // to inflate the price of the share
+    let pool_amount = Uint128::from(1001001u128);
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount,
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128)),
+                (&"attacker0000".to_string(), &Uint128::from(1u128)),
+            ],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+    let info = mock_info("attacker0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Sync {}).unwrap();
+
+    // without the locked share the victim would get 500000 * 1 / 1001001 = 0 share,
+    // with it the victim gets 500000 * 1001 / 1001001 = 499 share
+    let info = mock_info(
+        "victim0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, provide_msg(500000)).unwrap();
+    assert_eq!(res.attributes[4], attr("share", "499"));
+
+    deps.querier.with_token_balances(&[(
+        &"liquidity0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128)),
+            (&"attacker0000".to_string(), &Uint128::from(1u128)),
+            (&"victim0000".to_string(), &Uint128::from(499u128)),
+        ],
+    )]);
+
+    // the donation is mostly captured by the locked share,
+    // the attacker can only withdraw 1000 of the 1001001 spent
+    let res = query_simulate_withdraw_liquidity(deps.as_ref(), Uint128::from(1u128)).unwrap();
+    assert_eq!(res.refund_assets[0].amount, Uint128::from(1000u128));
+    assert_eq!(res.refund_assets[1].amount, Uint128::from(1000u128));
+
+    // while the victim loses less than 0.2% to rounding
+    let res = query_simulate_withdraw_liquidity(deps.as_ref(), Uint128::from(499u128)).unwrap();
+    assert_eq!(res.refund_assets[0].amount, Uint128::from(499332u128));
+    assert_eq!(res.refund_assets[1].amount, Uint128::from(499332u128));
+}
+
+#[test]
+fn flash_swap() {
+    let pool_amount = Uint128::from(1000000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    RESERVES
+        .save(&mut deps.storage, &[pool_amount, pool_amount])
+        .unwrap();
+
+    // cannot borrow the whole pool
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: pool_amount,
+        },
+        callback_msg: to_binary(&"callback").unwrap(),
+    };
+    let info = mock_info("borrower0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "ask amount exceeds the pool")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        callback_msg: to_binary(&"callback").unwrap(),
+    };
+    let info = mock_info("borrower0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "borrower0000".to_string(),
+                    amount: Uint128::from(100000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg {
+                id: 2,
+                msg: WasmMsg::Execute {
+                    contract_addr: "borrower0000".to_string(),
+                    msg: to_binary(&"callback").unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            },
+        ]
+    );
+
+    // the pair is locked until the callback replies
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "borrower0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::FlashSwapInProgress {}));
+
+    // keeping the product needs (1 - 0.003) * paid >= 1000000 * 1000000 / 900000 - 1000000,
+    // so 111445uusd is not enough
+    let flash_swap_reply = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1111445u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(900000u128))],
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), flash_swap_reply.clone());
+    assert_eq!(res, Err(ContractError::FlashSwapInvariantViolation {}));
+
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1111446u128),
+        }],
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), flash_swap_reply).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_swap_repaid"),
+            attr("borrower", "borrower0000"),
+            attr("reserves", "1111446uusd, 900000asset0000"),
+            attr("protocol_fee_assets", "0uusd, 0asset0000"),
+        ]
+    );
+
+    // the reserves follow the balances and the pair is unlocked
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(1111446u128), Uint128::from(900000u128)]
+    );
+    let msg = ExecuteMsg::Sync {};
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn stable_flash_swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable),
+        pair_type_name: None,
+        amp: Some(100u64),
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // an imbalanced pool
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(2000000u128), Uint128::from(1000000u128)],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        callback_msg: to_binary(&"callback").unwrap(),
+    };
+    let info = mock_info("borrower0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 53000asset0000 keeps the constant product but not the stable invariant,
+    // the borrowed uusd could be swapped back for almost 100000asset0000
+    let flash_swap_reply = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1900000u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1053000u128))],
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), flash_swap_reply.clone());
+    assert_eq!(res, Err(ContractError::FlashSwapInvariantViolation {}));
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1099938u128))],
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), flash_swap_reply.clone());
+    assert_eq!(res, Err(ContractError::FlashSwapInvariantViolation {}));
+
+    // 10% of the commission of 299asset0000 goes to the collector
+    deps.querier
+        .with_protocol_fee(Decimal::percent(10), Some("collector0000".to_string()));
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1099939u128))],
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), flash_swap_reply).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "collector0000".to_string(),
+                amount: Uint128::from(29u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_swap_repaid"),
+            attr("borrower", "borrower0000"),
+            attr("reserves", "1900000uusd, 1099910asset0000"),
+            attr("protocol_fee_assets", "0uusd, 29asset0000"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(1900000u128), Uint128::from(1099910u128)]
+    );
+}
+
+#[test]
+fn swap_exact_out() {
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let max_offer_amount = Uint128::from(2000000000u128);
+    let ask_amount = Uint128::from(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + max_offer_amount,
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &collateral_pool_amount)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: ask_amount,
+        },
+    )
+    .unwrap();
+
+    // the offer is lower than the reverse simulation
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer_amount: reverse_simulation_res.offer_amount,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: reverse_simulation_res.offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::MaxOfferAmountAssertion {}));
+
+    // tokens can only be offered through the hook
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer_amount,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer_amount,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: max_offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the reverse simulation rounds down, one more is taken to cover the ask amount
+    let offer_amount = reverse_simulation_res.offer_amount + Uint128::from(1u128);
+    assert_eq!(offer_amount, Uint128::from(1583949314u128));
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: ask_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: max_offer_amount - offer_amount,
+                }],
+            })),
+        ]
+    );
+    assert_eq!(res.attributes[5], attr("offer_amount", "1583949314"));
+    assert_eq!(res.attributes[7], attr("refund_amount", "416050686"));
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [
+            collateral_pool_amount + offer_amount,
+            asset_pool_amount - ask_amount
+        ]
+    );
+
+    // buy native with the sent token
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: max_offer_amount,
+        msg: to_binary(&Cw20HookMsg::SwapExactOut {
+            ask_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: ask_amount,
+            },
+            to: Some("third_party".to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "third_party".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: ask_amount,
+            }],
+        }))
+    );
+    assert_eq!(res.attributes[0], attr("action", "swap_exact_out"));
+}
+
+#[test]
+fn paused_pair() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        commission_rate: None,
+        pair_type: None,
+        pair_type_name: None,
+        amp: None,
+        asset_decimals: [6, 6],
+    };
+
+    // addr0000 acts as the factory
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    RESERVES
+        .save(
+            &mut deps.storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // only the factory can pause the pair
+    let msg = ExecuteMsg::SetStatus { paused: true };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "set_status"), attr("paused", "true")]
+    );
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert!(pair_info.paused);
+
+    // swaps are blocked
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(10u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Paused {}));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(10u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Paused {}));
+
+    // deposits are blocked
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(10u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(10u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Paused {}));
+
+    // withdrawals are still allowed
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "withdraw_liquidity"));
+
+    // resume the pair
+    let msg = ExecuteMsg::SetStatus { paused: false };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert!(!pair_info.paused);
+}
+
+/// Pair info layout before the pause flag was added
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct LegacyPairInfoRaw {
+    pub asset_infos: [AssetInfoRaw; 2],
+    pub contract_addr: CanonicalAddr,
+    pub liquidity_token: CanonicalAddr,
+}
+
+#[test]
+fn migrate_legacy_pair() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
+    )]);
+
+    // pair created before the version, the config and the reserves were stored
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+    ];
+    Item::<LegacyPairInfoRaw>::new("\u{0}\u{9}pair_info")
+        .save(
+            &mut deps.storage,
+            &LegacyPairInfoRaw {
+                asset_infos: [
+                    asset_infos[0].to_raw(&deps.api).unwrap(),
+                    asset_infos[1].to_raw(&deps.api).unwrap(),
+                ],
+                contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+                liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            },
+        )
+        .unwrap();
+
+    // the factory is required to build the legacy config
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { factory: None });
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "factory is required to migrate a legacy pair")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            factory: Some("factory0000".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[0], attr("action", "migrate"));
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(pair_info.asset_infos, asset_infos);
+    assert_eq!(pair_info.liquidity_token, "liquidity0000".to_string());
+    assert!(!pair_info.paused);
+
+    let config_res: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(
+        config_res,
+        ConfigResponse {
+            factory: "factory0000".to_string(),
+            commission_rate: Decimal::permille(3),
+            pair_type: PairType::Xyk,
+            amp: None,
+        }
+    );
+    assert_eq!(
+        RESERVES.load(&deps.storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(200u128)]
+    );
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:terraswap-pair");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // the config of a versioned pair is left untouched
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            factory: Some("factory0001".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        CONFIG.load(&deps.storage).unwrap().factory,
+        deps.api.addr_canonicalize("factory0000").unwrap()
+    );
+
+    // downgrades are refused
+    set_contract_version(&mut deps.storage, "crates.io:terraswap-pair", "99.0.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { factory: None });
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            format!(
+                "cannot migrate from 99.0.0 to the older version {}",
+                env!("CARGO_PKG_VERSION")
+            )
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -3,6 +3,7 @@ use cosmwasm_std::{
     StdResult, WasmMsg,
 };
 
+use crate::error::ContractError;
 use crate::querier::compute_tax;
 use crate::state::{Config, CONFIG};
 
@@ -21,9 +22,9 @@ pub fn execute_swap_operation(
     info: MessageInfo,
     operation: SwapOperation,
     to: Option<String>,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     if env.contract.address != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(StdError::generic_err("unauthorized").into());
     }
 
     let messages: Vec<CosmosMsg<TerraMsgWrapper>> = match operation {
@@ -123,6 +124,7 @@ pub fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             }))
         }
@@ -137,6 +139,7 @@ pub fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
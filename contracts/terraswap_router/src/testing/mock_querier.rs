@@ -195,6 +195,9 @@ impl WasmMockQuerier {
                                     denom: "uusd".to_string(),
                                 },
                             ],
+                            paused: false,
+                            pair_type: "xyk".to_string(),
+                            asset_decimals: [6, 6],
                         }))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
                             error: "No pair info exists".to_string(),
@@ -207,6 +210,7 @@ impl WasmMockQuerier {
                         return_amount: offer_asset.amount,
                         commission_amount: Uint128::zero(),
                         spread_amount: Uint128::zero(),
+                        protocol_fee_amount: Uint128::zero(),
                     })))
                 }
                 _ => match from_binary(msg).unwrap() {
@@ -1,17 +1,20 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Api, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
 };
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG};
 use crate::testing::mock_querier::mock_dependencies;
 
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use terra_cosmwasm::{create_swap_msg, create_swap_send_msg};
 use terraswap::asset::{Asset, AssetInfo};
 use terraswap::pair::ExecuteMsg as PairExecuteMsg;
 use terraswap::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
     SimulateSwapOperationsResponse, SwapOperation,
 };
 
@@ -55,15 +58,33 @@ fn execute_swap_operations() {
         operations: vec![],
         minimum_receive: None,
         to: None,
+        deadline: None,
     };
 
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "must provide operations"),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "must provide operations")
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    let env = mock_env();
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "ukrw".to_string(),
+        }],
+        minimum_receive: None,
+        to: None,
+        deadline: Some(env.block.time.seconds() - 1),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(res, Err(ContractError::Expired {}));
+
     let msg = ExecuteMsg::ExecuteSwapOperations {
         operations: vec![
             SwapOperation::NativeSwap {
@@ -97,6 +118,7 @@ fn execute_swap_operations() {
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
+        deadline: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -216,6 +238,7 @@ fn execute_swap_operations() {
             ],
             minimum_receive: None,
             to: Some("addr0002".to_string()),
+            deadline: None,
         })
         .unwrap(),
     });
@@ -325,7 +348,9 @@ fn execute_swap_operation() {
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "unauthorized")
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -403,6 +428,7 @@ fn execute_swap_operation() {
                     belief_price: None,
                     max_spread: None,
                     to: Some("addr0000".to_string()),
+                    deadline: None,
                 })
                 .unwrap()
             })
@@ -531,7 +557,7 @@ fn assert_minimum_receive_native_token() {
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
             msg,
             "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
         ),
@@ -570,10 +596,50 @@ fn assert_minimum_receive_token() {
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
             msg,
             "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
         ),
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+#[test]
+fn migrate_legacy_router() {
+    let mut deps = mock_dependencies(&[]);
+
+    // router instantiated before the version was stored
+    CONFIG
+        .save(
+            &mut deps.storage,
+            &Config {
+                terraswap_factory: deps.api.addr_canonicalize("terraswapfactory").unwrap(),
+            },
+        )
+        .unwrap();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "migrate"));
+
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!("terraswapfactory", config_res.terraswap_factory.as_str());
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:terraswap-router");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // downgrades are refused
+    set_contract_version(&mut deps.storage, "crates.io:terraswap-router", "99.0.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            format!(
+                "cannot migrate from 99.0.0 to the older version {}",
+                env!("CARGO_PKG_VERSION")
+            )
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::pair::DEFAULT_PAIR_TYPE;
 use crate::querier::{query_balance, query_token_balance};
 use cosmwasm_std::{
     to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal, MessageInfo,
@@ -273,6 +274,19 @@ pub struct PairInfo {
     pub asset_infos: [AssetInfo; 2],
     pub contract_addr: String,
     pub liquidity_token: String,
+    /// Swaps and deposits are blocked while the pair is paused
+    #[serde(default)]
+    pub paused: bool,
+    /// Name of the pair type in the factory
+    #[serde(default = "default_pair_type")]
+    pub pair_type: String,
+    /// Decimals of the assets, zero for pairs created before they were recorded
+    #[serde(default)]
+    pub asset_decimals: [u8; 2],
+}
+
+fn default_pair_type() -> String {
+    DEFAULT_PAIR_TYPE.to_string()
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -280,6 +294,12 @@ pub struct PairInfoRaw {
     pub asset_infos: [AssetInfoRaw; 2],
     pub contract_addr: CanonicalAddr,
     pub liquidity_token: CanonicalAddr,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default = "default_pair_type")]
+    pub pair_type: String,
+    #[serde(default)]
+    pub asset_decimals: [u8; 2],
 }
 
 impl PairInfoRaw {
@@ -291,6 +311,9 @@ impl PairInfoRaw {
                 self.asset_infos[0].to_normal(api)?,
                 self.asset_infos[1].to_normal(api)?,
             ],
+            paused: self.paused,
+            pair_type: self.pair_type.clone(),
+            asset_decimals: self.asset_decimals,
         })
     }
 
@@ -1,13 +1,23 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::{AssetInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::pair::PairType;
+
+use cosmwasm_std::Decimal;
+use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    /// Pair contract code ID, which is used to
+    /// Pair contract code ID of the default xyk pair type
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    /// Commission rate of the default xyk pair type, defaults to 0.3%
+    pub commission_rate: Option<Decimal>,
+    /// Share of the commission sent to the fee collector, defaults to zero
+    pub protocol_fee_share: Option<Decimal>,
+    /// Recipient of the protocol fee
+    pub fee_collector: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -15,14 +25,139 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// UpdateConfig update relevant code IDs
     UpdateConfig {
-        owner: Option<String>,
         token_code_id: Option<u64>,
-        pair_code_id: Option<u64>,
+        protocol_fee_share: Option<Decimal>,
+        fee_collector: Option<String>,
+        /// Restricts the pair creation to whitelisted assets
+        permissioned: Option<bool>,
+        /// Fee charged for each pair creation, a zero amount removes it
+        creation_fee: Option<Asset>,
+        /// Recipient of the creation fee
+        treasury: Option<String>,
+    },
+    Receive(Cw20ReceiveMsg),
+    /// UpdatePairType registers a pair type or replaces its code and default parameters,
+    /// pairs already created keep theirs. The curve of a registered type cannot change
+    UpdatePairType {
+        pair_type: String,
+        code_id: u64,
+        curve: PairType,
+        /// Commission rate of the new pairs of this type,
+        /// kept when omitted for a registered type and 0.3% for a new one
+        commission_rate: Option<Decimal>,
+    },
+    /// RemovePairType stops the creation of pairs of a type, the registered pairs are kept
+    RemovePairType {
+        pair_type: String,
     },
     /// CreatePair instantiates pair contract
     CreatePair {
         /// Asset infos
         asset_infos: [AssetInfo; 2],
+        /// Name of a registered pair type, defaults to xyk
+        pair_type: Option<String>,
+        /// Amplification coefficient, required for stable pairs
+        amp: Option<u64>,
+    },
+    /// CreatePairWithLiquidity creates a pair and provides its first liquidity,
+    /// token assets must be approved to the factory
+    CreatePairWithLiquidity {
+        assets: [Asset; 2],
+        slippage_tolerance: Option<Decimal>,
+        /// Recipient of the LP tokens, defaults to the sender
+        receiver: Option<String>,
+        /// Name of a registered pair type, defaults to xyk
+        pair_type: Option<String>,
+        /// Amplification coefficient, required for stable pairs
+        amp: Option<u64>,
+    },
+    /// UpdatePairConfig updates the parameters of a registered pair
+    UpdatePairConfig {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+        commission_rate: Option<Decimal>,
+    },
+    /// RampPairAmp ramps the amplification coefficient of a registered stable pair
+    RampPairAmp {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+        future_amp: u64,
+        future_time: u64,
+    },
+    /// StopPairAmpRamp stops the ongoing amplification ramp of a registered stable pair
+    StopPairAmpRamp {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+    },
+    /// SetPairStatus pauses or resumes swaps and deposits of a registered pair
+    SetPairStatus {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+        paused: bool,
+    },
+    /// MigratePairs migrates a page of the registered pairs of `pair_type` to `code_id`,
+    /// the factory is the admin of every pair it creates
+    MigratePairs {
+        code_id: u64,
+        /// Name of the pair type to migrate, defaults to xyk
+        pair_type: Option<String>,
+        start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of the `start_after` pair, defaults to xyk
+        start_after_pair_type: Option<String>,
+        limit: Option<u32>,
+    },
+    /// IndexPairs indexes a page of registered pairs by asset and by address,
+    /// for the pairs registered before the indexes were added
+    IndexPairs {
+        start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of the `start_after` pair, defaults to xyk
+        start_after_pair_type: Option<String>,
+        limit: Option<u32>,
+    },
+    /// DeregisterPair removes a pair from the registry, the pair contract keeps working
+    DeregisterPair {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+    },
+    /// RegisterExistingPair adds an instantiated pair contract to the registry
+    RegisterExistingPair {
+        pair_addr: String,
+    },
+    /// ProposeNewOwner proposes a new owner, who has `expires_in` seconds to claim the ownership
+    ProposeNewOwner {
+        owner: String,
+        expires_in: u64,
+    },
+    /// ClaimOwnership makes the proposed owner the owner of the factory
+    ClaimOwnership {},
+    /// DropOwnershipProposal removes the pending ownership proposal
+    DropOwnershipProposal {},
+    /// UpdateAssetWhitelist updates the assets allowed in new pairs while the creation is permissioned
+    UpdateAssetWhitelist {
+        add: Vec<AssetInfo>,
+        remove: Vec<AssetInfo>,
+    },
+    /// UpdateAssetBlacklist updates the assets never allowed in new pairs
+    UpdateAssetBlacklist {
+        add: Vec<AssetInfo>,
+        remove: Vec<AssetInfo>,
+    },
+    /// AddNativeTokenDecimals registers the decimals of a native token,
+    /// a deposit of the denom must be sent to prove it exists
+    AddNativeTokenDecimals {
+        denom: String,
+        decimals: u8,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// CreatePair instantiates pair contract, paying the creation fee with the sent token
+    CreatePair {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<String>,
+        amp: Option<u64>,
     },
 }
 
@@ -32,19 +167,67 @@ pub enum QueryMsg {
     Config {},
     Pair {
         asset_infos: [AssetInfo; 2],
+        /// Name of the pair type, defaults to xyk
+        pair_type: Option<String>,
     },
     Pairs {
         start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of the `start_after` pair, defaults to xyk
+        start_after_pair_type: Option<String>,
+        limit: Option<u32>,
+    },
+    /// PairsByAsset returns the pairs containing `asset_info`
+    PairsByAsset {
+        asset_info: AssetInfo,
+        start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of the `start_after` pair, defaults to xyk
+        start_after_pair_type: Option<String>,
+        limit: Option<u32>,
+    },
+    /// PairByAddress returns the pair registered with the pair contract address
+    PairByAddress {
+        contract_addr: String,
+    },
+    /// PairByLiquidityToken returns the pair registered with the liquidity token address
+    PairByLiquidityToken {
+        liquidity_token: String,
+    },
+    /// AssetWhitelist returns the assets allowed in new pairs while the creation is permissioned
+    AssetWhitelist {
+        start_after: Option<AssetInfo>,
         limit: Option<u32>,
     },
+    /// NativeTokenDecimals returns the registered decimals of a native token
+    NativeTokenDecimals {
+        denom: String,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
+    /// Pair contract code ID of the default xyk pair type
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    /// Commission rate of the default xyk pair type
+    pub commission_rate: Decimal,
+    pub protocol_fee_share: Decimal,
+    pub fee_collector: Option<String>,
+    pub permissioned: bool,
+    pub creation_fee: Option<Asset>,
+    pub treasury: Option<String>,
+    pub pair_types: Vec<PairTypeConfig>,
+}
+
+/// PairTypeConfig holds the code and the default parameters of the pairs of a type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairTypeConfig {
+    pub pair_type: String,
+    pub code_id: u64,
+    /// Pricing curve of the pairs
+    pub curve: PairType,
+    pub commission_rate: Decimal,
 }
 
 /// We currently take no arguments for migrations
@@ -56,3 +239,13 @@ pub struct MigrateMsg {}
 pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetWhitelistResponse {
+    pub assets: Vec<AssetInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NativeTokenDecimalsResponse {
+    pub decimals: u8,
+}
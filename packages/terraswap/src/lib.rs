@@ -1,9 +1,11 @@
 pub mod asset;
 pub mod factory;
+pub mod ownership;
 pub mod pair;
 pub mod querier;
 pub mod router;
 pub mod token;
+pub mod version;
 
 #[cfg(test)]
 mod mock_querier;
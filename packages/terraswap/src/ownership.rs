@@ -0,0 +1,63 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Api, Env, MessageInfo, StdError, StdResult};
+
+/// Longest validity of an ownership proposal == 14 days
+pub const MAX_PROPOSAL_TTL: u64 = 1_209_600;
+
+/// Pending transfer of an admin role, the role only moves once the proposed
+/// address claims it before `expires_at`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    pub owner: Addr,
+    pub expires_at: u64,
+}
+
+/// Builds the proposal of `new_owner`, valid for `expires_in` seconds.
+/// The caller must check the sender is the current owner.
+pub fn propose_new_owner(
+    api: &dyn Api,
+    env: &Env,
+    owner: &Addr,
+    new_owner: &str,
+    expires_in: u64,
+) -> StdResult<OwnershipProposal> {
+    let new_owner = api.addr_validate(new_owner)?;
+    if new_owner == *owner {
+        return Err(StdError::generic_err(
+            "new owner cannot be the current owner",
+        ));
+    }
+
+    if expires_in > MAX_PROPOSAL_TTL {
+        return Err(StdError::generic_err(format!(
+            "expires_in cannot be greater than {}",
+            MAX_PROPOSAL_TTL
+        )));
+    }
+
+    Ok(OwnershipProposal {
+        owner: new_owner,
+        expires_at: env.block.time.seconds() + expires_in,
+    })
+}
+
+/// Checks the sender can claim the proposed role and returns the new owner
+pub fn claim_ownership(
+    env: &Env,
+    info: &MessageInfo,
+    proposal: Option<OwnershipProposal>,
+) -> StdResult<Addr> {
+    let proposal = proposal.ok_or_else(|| StdError::generic_err("ownership proposal not found"))?;
+
+    if info.sender != proposal.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if env.block.time.seconds() > proposal.expires_at {
+        return Err(StdError::generic_err("ownership proposal expired"));
+    }
+
+    Ok(proposal.owner)
+}
@@ -1,9 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::asset::{Asset, AssetInfo};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{Binary, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -12,6 +14,40 @@ pub struct InstantiateMsg {
     pub asset_infos: [AssetInfo; 2],
     /// Token contract code id for initialization
     pub token_code_id: u64,
+    /// Commission rate charged on every swap, defaults to 0.3%
+    pub commission_rate: Option<Decimal>,
+    /// Pricing curve of the pair, defaults to xyk
+    pub pair_type: Option<PairType>,
+    /// Name of the pair type in the factory, defaults to the name of the curve
+    pub pair_type_name: Option<String>,
+    /// Amplification coefficient, required for stable pairs
+    pub amp: Option<u64>,
+    /// Decimals of the assets, in the order of `asset_infos`.
+    /// Defaults to zeros, which treats both assets with the same precision
+    #[serde(default)]
+    pub asset_decimals: [u8; 2],
+}
+
+/// Name of the pair type used when none is given
+pub const DEFAULT_PAIR_TYPE: &str = "xyk";
+
+/// PairType selects the invariant used to price swaps
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairType {
+    /// Constant product `x * y = k`
+    Xyk,
+    /// StableSwap invariant for pegged assets
+    Stable,
+}
+
+impl fmt::Display for PairType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairType::Xyk => write!(f, "xyk"),
+            PairType::Stable => write!(f, "stable"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,6 +59,17 @@ pub enum ExecuteMsg {
         assets: [Asset; 2],
         slippage_tolerance: Option<Decimal>,
         receiver: Option<String>,
+        /// Latest block time (unix seconds) the message can be executed at
+        deadline: Option<u64>,
+    },
+    /// ProvideSingleSided swaps part of a native asset to the other one
+    /// and provides both as liquidity
+    ProvideSingleSided {
+        asset: Asset,
+        /// Max spread of the internal swap
+        slippage_tolerance: Option<Decimal>,
+        receiver: Option<String>,
+        deadline: Option<u64>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -30,7 +77,44 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// SwapExactOut buys exactly `ask_asset` with at most `max_offer_amount`
+    /// of the other asset, the unused offer is refunded to the sender
+    SwapExactOut {
+        ask_asset: Asset,
+        max_offer_amount: Uint128,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// FlashSwap sends `ask_asset` to the sender and executes `callback_msg` on it,
+    /// the callback must pay the pair back so that the invariant of the pair
+    /// including the commission does not decrease
+    FlashSwap {
+        ask_asset: Asset,
+        callback_msg: Binary,
+    },
+    /// UpdateConfig updates the pair parameters, only the factory can execute it
+    UpdateConfig {
+        commission_rate: Option<Decimal>,
     },
+    /// RampAmp moves the amplification coefficient of a stable pair linearly
+    /// until `future_time`, only the factory can execute it
+    RampAmp {
+        future_amp: u64,
+        future_time: u64,
+    },
+    /// StopRampAmp freezes the amplification coefficient at its current value
+    StopRampAmp {},
+    /// SetStatus pauses or resumes swaps and deposits, withdrawals stay open,
+    /// only the factory can execute it
+    SetStatus {
+        paused: bool,
+    },
+    /// Skim sends the balances exceeding the reserves to the sender
+    Skim {},
+    /// Sync sets the reserves to the balances of the pair
+    Sync {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,8 +125,26 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Buy exactly `ask_asset` with at most the sent amount
+    SwapExactOut {
+        ask_asset: Asset,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Provide liquidity with the sent token only
+    ProvideSingleSided {
+        slippage_tolerance: Option<Decimal>,
+        receiver: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity, failing if a refund is below its `min_assets` entry
+    /// or the block time is past `deadline` (unix seconds)
+    WithdrawLiquidity {
+        min_assets: Option<[Asset; 2]>,
+        deadline: Option<u64>,
     },
-    WithdrawLiquidity {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -50,8 +152,13 @@ pub enum Cw20HookMsg {
 pub enum QueryMsg {
     Pair {},
     Pool {},
+    Config {},
     Simulation { offer_asset: Asset },
     ReverseSimulation { ask_asset: Asset },
+    CumulativePrices {},
+    Twap { seconds_ago: u64 },
+    SimulateWithdrawLiquidity { lp_amount: Uint128 },
+    SimulateProvideSingleSided { asset: Asset },
 }
 
 // We define a custom struct for each query response
@@ -61,12 +168,63 @@ pub struct PoolResponse {
     pub total_share: Uint128,
 }
 
+/// ConfigResponse returns the pair parameters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub factory: String,
+    pub commission_rate: Decimal,
+    pub pair_type: PairType,
+    /// Current amplification coefficient of a stable pair
+    pub amp: Option<u64>,
+}
+
+/// CumulativePricesResponse returns the price accumulators as of the current block.
+/// `price0` is the price of `asset_infos[0]` in `asset_infos[1]` and `price1` the inverse.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CumulativePricesResponse {
+    pub assets: [Asset; 2],
+    pub price0_cumulative_last: Decimal256,
+    pub price1_cumulative_last: Decimal256,
+    pub block_time_last: u64,
+}
+
+/// TwapResponse returns the average prices between the
+/// snapshot taken at `from_time` and the current block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TwapResponse {
+    pub asset_infos: [AssetInfo; 2],
+    pub price0_average: Decimal256,
+    pub price1_average: Decimal256,
+    pub from_time: u64,
+    pub to_time: u64,
+}
+
+/// SimulateWithdrawLiquidityResponse returns the assets refunded for burning `lp_amount`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawLiquidityResponse {
+    pub refund_assets: [Asset; 2],
+}
+
+/// SimulateProvideSingleSidedResponse returns how a single sided provision
+/// is split between the internal swap and the deposit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateProvideSingleSidedResponse {
+    /// Part of the asset swapped to the other asset
+    pub swap_amount: Uint128,
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+    pub share: Uint128,
+}
+
 /// SimulationResponse returns swap simulation response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SimulationResponse {
     pub return_amount: Uint128,
     pub spread_amount: Uint128,
     pub commission_amount: Uint128,
+    /// Part of the commission sent to the fee collector
+    pub protocol_fee_amount: Uint128,
 }
 
 /// ReverseSimulationResponse returns reverse swap simulation response
@@ -77,6 +235,9 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Factory of a pair created before the pair config was stored,
+    /// ignored for other pairs
+    pub factory: Option<String>,
+}
@@ -1,5 +1,5 @@
 use crate::asset::{Asset, AssetInfo, PairInfo};
-use crate::factory::QueryMsg as FactoryQueryMsg;
+use crate::factory::{ConfigResponse as FactoryConfigResponse, QueryMsg as FactoryQueryMsg};
 use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
 
 use cosmwasm_std::{
@@ -56,6 +56,15 @@ pub fn query_supply(querier: &QuerierWrapper, contract_addr: Addr) -> StdResult<
     Ok(token_info.total_supply)
 }
 
+pub fn query_token_decimals(querier: &QuerierWrapper, contract_addr: Addr) -> StdResult<u8> {
+    let token_info: TokenInfoResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }))?;
+
+    Ok(token_info.decimals)
+}
+
 pub fn query_pair_info(
     querier: &QuerierWrapper,
     factory_contract: Addr,
@@ -65,10 +74,21 @@ pub fn query_pair_info(
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         })?,
     }))
 }
 
+pub fn query_factory_config(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+) -> StdResult<FactoryConfigResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::Config {})?,
+    }))
+}
+
 pub fn simulate(
     querier: &QuerierWrapper,
     pair_contract: Addr,
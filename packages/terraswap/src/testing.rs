@@ -2,10 +2,16 @@ use crate::asset::{Asset, AssetInfo, PairInfo};
 use crate::mock_querier::mock_dependencies;
 use crate::querier::{
     query_all_balances, query_balance, query_pair_info, query_supply, query_token_balance,
+    query_token_decimals,
 };
 
-use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Uint128, WasmMsg};
+use crate::version::assert_migrate_version;
+
+use cosmwasm_std::testing::{MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, StdError, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -91,7 +97,11 @@ fn supply_querier() {
     assert_eq!(
         query_supply(&deps.as_ref().querier, Addr::unchecked("liquidity0000")).unwrap(),
         Uint128::from(492u128)
-    )
+    );
+    assert_eq!(
+        query_token_decimals(&deps.as_ref().querier, Addr::unchecked("liquidity0000")).unwrap(),
+        6u8
+    );
 }
 
 #[test]
@@ -255,6 +265,9 @@ fn query_terraswap_pair_contract() {
             ],
             contract_addr: "pair0000".to_string(),
             liquidity_token: "liquidity0000".to_string(),
+            paused: false,
+            pair_type: "xyk".to_string(),
+            asset_decimals: [6, 6],
         },
     )]);
 
@@ -275,3 +288,36 @@ fn query_terraswap_pair_contract() {
     assert_eq!(pair_info.contract_addr, Addr::unchecked("pair0000"),);
     assert_eq!(pair_info.liquidity_token, Addr::unchecked("liquidity0000"),);
 }
+
+#[test]
+fn migrate_version() {
+    let mut storage = MockStorage::new();
+
+    // contracts without a stored version are legacy
+    assert_eq!(
+        assert_migrate_version(&storage, "crates.io:terraswap-pair", "1.0.0").unwrap(),
+        None
+    );
+
+    set_contract_version(&mut storage, "crates.io:terraswap-pair", "1.0.0").unwrap();
+    let stored = assert_migrate_version(&storage, "crates.io:terraswap-pair", "1.0.0")
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.version, "1.0.0");
+    assert!(assert_migrate_version(&storage, "crates.io:terraswap-pair", "1.1.0").is_ok());
+
+    match assert_migrate_version(&storage, "crates.io:terraswap-pair", "0.9.0") {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "cannot migrate from 1.0.0 to the older version 0.9.0")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match assert_migrate_version(&storage, "crates.io:terraswap-factory", "1.0.0") {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "cannot migrate from crates.io:terraswap-pair to crates.io:terraswap-factory"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -0,0 +1,38 @@
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cw2::{ContractVersion, CONTRACT};
+use semver::Version;
+
+/// Checks the stored cw2 version before a migration and returns it,
+/// contracts instantiated before the version was recorded return `None`.
+/// Migrating from another contract or from a newer version is refused.
+pub fn assert_migrate_version(
+    storage: &dyn Storage,
+    contract_name: &str,
+    contract_version: &str,
+) -> StdResult<Option<ContractVersion>> {
+    let stored = match CONTRACT.may_load(storage)? {
+        Some(stored) => stored,
+        None => return Ok(None),
+    };
+
+    if stored.contract != contract_name {
+        return Err(StdError::generic_err(format!(
+            "cannot migrate from {} to {}",
+            stored.contract, contract_name
+        )));
+    }
+
+    if parse_version(&stored.version)? > parse_version(contract_version)? {
+        return Err(StdError::generic_err(format!(
+            "cannot migrate from {} to the older version {}",
+            stored.version, contract_version
+        )));
+    }
+
+    Ok(Some(stored))
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|_| StdError::generic_err(format!("invalid version {}", version)))
+}